@@ -0,0 +1,243 @@
+use crate::{
+    game::CardComponent,
+    utils::constants::{CARD_HEIGHT, CARD_WIDTH},
+    Settings,
+};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use serde::{Deserialize, Serialize};
+use uno::{card::Color, texts::TextId};
+
+/// Colors a player can choose after a wild card, in the order of the color picker
+pub const PLAYABLE_COLORS: [Color; 4] = [Color::Yellow, Color::Red, Color::Blue, Color::Green];
+
+pub struct AccessibilityPlugin;
+
+/// Colors used by the interface to show the color of the cards
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Palette {
+    /// Colors of the cards
+    Classic,
+    /// Okabe-Ito colors, told apart with the common color blindnesses
+    ColorblindSafe,
+    /// Saturated colors with very different lightnesses
+    HighContrast,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 3] = [
+        Palette::Classic,
+        Palette::ColorblindSafe,
+        Palette::HighContrast,
+    ];
+
+    pub fn text_id(self) -> TextId {
+        match self {
+            Palette::Classic => TextId::PaletteClassic,
+            Palette::ColorblindSafe => TextId::PaletteColorblindSafe,
+            Palette::HighContrast => TextId::PaletteHighContrast,
+        }
+    }
+
+    pub fn color32(self, color: Color) -> egui::Color32 {
+        match (self, color) {
+            (_, Color::Black) => egui::Color32::BLACK,
+            (Palette::Classic, Color::Yellow) => egui::Color32::from_rgb(255, 255, 22),
+            (Palette::Classic, Color::Red) => egui::Color32::from_rgb(237, 28, 36),
+            (Palette::Classic, Color::Blue) => egui::Color32::from_rgb(0, 114, 188),
+            (Palette::Classic, Color::Green) => egui::Color32::from_rgb(80, 170, 68),
+            (Palette::ColorblindSafe, Color::Yellow) => egui::Color32::from_rgb(240, 228, 66),
+            (Palette::ColorblindSafe, Color::Red) => egui::Color32::from_rgb(213, 94, 0),
+            (Palette::ColorblindSafe, Color::Blue) => egui::Color32::from_rgb(0, 114, 178),
+            (Palette::ColorblindSafe, Color::Green) => egui::Color32::from_rgb(0, 158, 115),
+            (Palette::HighContrast, Color::Yellow) => egui::Color32::from_rgb(255, 255, 0),
+            (Palette::HighContrast, Color::Red) => egui::Color32::from_rgb(200, 0, 0),
+            (Palette::HighContrast, Color::Blue) => egui::Color32::from_rgb(0, 0, 160),
+            (Palette::HighContrast, Color::Green) => egui::Color32::from_rgb(0, 220, 0),
+        }
+    }
+}
+
+// Components
+/// Shape drawn on a card so that its color doesn't have to be seen
+#[derive(Component)]
+pub struct ColorSymbol;
+
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(ui_theme).add_system(card_symbols);
+    }
+}
+
+/// Paint the symbol of a color in the middle of a rect: a bar for yellow, a square for red, a
+/// diamond for blue and a cross for green
+pub fn paint_color_symbol(painter: &egui::Painter, rect: egui::Rect, color: Color) {
+    let center = rect.center();
+    let size = rect.width().min(rect.height()) * 0.6;
+    let stroke_color = egui::Color32::BLACK;
+
+    for (offset, width, height) in symbol_rects(color) {
+        painter.rect_filled(
+            egui::Rect::from_center_size(
+                center + egui::Vec2::new(offset.x, -offset.y) * size,
+                egui::Vec2::new(width, height) * size,
+            ),
+            0.0,
+            stroke_color,
+        );
+    }
+
+    if color == Color::Blue {
+        let half = size / 2.0;
+        painter.add(egui::Shape::convex_polygon(
+            vec![
+                center + egui::Vec2::new(0.0, -half),
+                center + egui::Vec2::new(half, 0.0),
+                center + egui::Vec2::new(0.0, half),
+                center + egui::Vec2::new(-half, 0.0),
+            ],
+            stroke_color,
+            egui::Stroke::none(),
+        ));
+    }
+}
+
+/// Rects making up the symbol of a color, with their center and size relative to the size of
+/// the symbol. The blue diamond is a rotated square, drawn separately
+fn symbol_rects(color: Color) -> Vec<(Vec2, f32, f32)> {
+    match color {
+        Color::Yellow => vec![(Vec2::ZERO, 1.0, 0.3)],
+        Color::Red => vec![(Vec2::ZERO, 0.8, 0.8)],
+        Color::Green => vec![(Vec2::ZERO, 1.0, 0.3), (Vec2::ZERO, 0.3, 1.0)],
+        Color::Blue | Color::Black => vec![],
+    }
+}
+
+/// Switch between the default egui theme and the high contrast one
+fn ui_theme(mut egui_context: ResMut<EguiContext>, settings: Res<Settings>) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    let visuals = if settings.high_contrast {
+        high_contrast_visuals()
+    } else {
+        egui::Visuals::dark()
+    };
+
+    egui_context.ctx_mut().set_visuals(visuals);
+}
+
+fn high_contrast_visuals() -> egui::Visuals {
+    let mut visuals = egui::Visuals::dark();
+    let white_stroke = egui::Stroke::new(2.0, egui::Color32::WHITE);
+
+    visuals.override_text_color = Some(egui::Color32::WHITE);
+    visuals.window_fill = egui::Color32::BLACK;
+    visuals.panel_fill = egui::Color32::BLACK;
+    visuals.extreme_bg_color = egui::Color32::BLACK;
+    visuals.window_stroke = white_stroke;
+    visuals.selection.bg_fill = egui::Color32::from_rgb(0, 90, 255);
+    visuals.selection.stroke = white_stroke;
+
+    for widget in [
+        &mut visuals.widgets.noninteractive,
+        &mut visuals.widgets.inactive,
+        &mut visuals.widgets.hovered,
+        &mut visuals.widgets.active,
+        &mut visuals.widgets.open,
+    ] {
+        widget.bg_stroke = white_stroke;
+        widget.fg_stroke = white_stroke;
+    }
+    visuals.widgets.inactive.bg_fill = egui::Color32::BLACK;
+    visuals.widgets.hovered.bg_fill = egui::Color32::from_gray(60);
+    visuals.widgets.active.bg_fill = egui::Color32::from_gray(90);
+
+    visuals
+}
+
+/// Draw the symbols on the new cards when the colorblind mode is enabled, and on every card
+/// when it gets enabled. Remove them all when it gets disabled
+fn card_symbols(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut enabled: Local<bool>,
+    new_cards_query: Query<(Entity, &CardComponent), Added<CardComponent>>,
+    cards_query: Query<(Entity, &CardComponent)>,
+    symbols_query: Query<Entity, With<ColorSymbol>>,
+) {
+    let toggled = settings.colorblind_mode != *enabled;
+    *enabled = settings.colorblind_mode;
+
+    if !settings.colorblind_mode {
+        if toggled {
+            for entity in symbols_query.iter() {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+        return;
+    }
+
+    let cards: Vec<_> = if toggled {
+        cards_query.iter().collect()
+    } else {
+        new_cards_query.iter().collect()
+    };
+
+    for (entity, card) in cards {
+        commands.entity(entity).with_children(|parent| {
+            spawn_symbol(parent, card.0.color);
+        });
+    }
+}
+
+/// Symbol in the top left corner of a card, on a white background to be seen on every color
+fn spawn_symbol(parent: &mut ChildBuilder, color: Color) {
+    const SYMBOL_SIZE: f32 = 36.0;
+
+    if color == Color::Black {
+        return;
+    }
+
+    let corner = Vec3::new(
+        -CARD_WIDTH / 2.0 + SYMBOL_SIZE,
+        CARD_HEIGHT / 2.0 - SYMBOL_SIZE,
+        0.001,
+    );
+
+    parent
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: bevy::prelude::Color::WHITE,
+                custom_size: Some(Vec2::splat(SYMBOL_SIZE * 1.4)),
+                ..Sprite::default()
+            },
+            transform: Transform::from_translation(corner),
+            ..SpriteBundle::default()
+        })
+        .insert(ColorSymbol)
+        .with_children(|background| {
+            let mut shapes: Vec<(Vec2, f32, f32, f32)> = symbol_rects(color)
+                .into_iter()
+                .map(|(offset, width, height)| (offset, width, height, 0.0))
+                .collect();
+            if color == Color::Blue {
+                // A diamond is a square turned by 45°, its diagonal is the size of the symbol
+                shapes.push((Vec2::ZERO, 0.7, 0.7, std::f32::consts::FRAC_PI_4));
+            }
+
+            for (offset, width, height, angle) in shapes {
+                background.spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: bevy::prelude::Color::BLACK,
+                        custom_size: Some(Vec2::new(width, height) * SYMBOL_SIZE),
+                        ..Sprite::default()
+                    },
+                    transform: Transform::from_translation((offset * SYMBOL_SIZE).extend(0.0001))
+                        .with_rotation(Quat::from_rotation_z(angle)),
+                    ..SpriteBundle::default()
+                });
+            }
+        });
+}
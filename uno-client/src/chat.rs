@@ -0,0 +1,174 @@
+use crate::{menu::LobbyState, offline::OfflineGame, GameState, PlayerId, Settings};
+use bevy::{ecs::schedule::ShouldRun, prelude::*};
+use bevy_egui::{egui, EguiContext};
+use naia_bevy_client::{events::MessageEvent, Client};
+use uno::{
+    chat::{sanitize_chat_message, ChatScope, EMOTES},
+    network::{protocol::SendChat, Channels, Protocol},
+    texts::{TextId, Texts},
+};
+
+pub struct ChatPlugin;
+
+struct ChatEntry {
+    username: String,
+    text: String,
+    scope: ChatScope,
+    own: bool,
+}
+
+// Ressources
+#[derive(Default)]
+pub struct Chat {
+    entries: Vec<ChatEntry>,
+    /// Number of messages received while the chat was closed
+    unread: usize,
+    open: bool,
+    draft: String,
+}
+
+impl Plugin for ChatPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Chat>()
+            .add_system(receive_chat)
+            .add_system_set(SystemSet::on_enter(LobbyState::LobbiesList).with_system(clear_chat))
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(run_if_chat_available)
+                    .with_system(chat_panel),
+            );
+    }
+}
+
+/// The chat is available in a lobby and during an online game
+fn run_if_chat_available(
+    lobby_state: Res<State<LobbyState>>,
+    game_state: Res<State<GameState>>,
+    offline_game: Option<Res<OfflineGame>>,
+) -> ShouldRun {
+    if offline_game.is_some() {
+        return ShouldRun::No;
+    }
+
+    match (game_state.current(), lobby_state.current()) {
+        (GameState::Game, _) | (GameState::Lobbies, LobbyState::InLobby(_)) => ShouldRun::Yes,
+        _ => ShouldRun::No,
+    }
+}
+
+fn current_scope(game_state: &State<GameState>) -> ChatScope {
+    if game_state.current() == &GameState::Game {
+        ChatScope::Game
+    } else {
+        ChatScope::Lobby
+    }
+}
+
+fn receive_chat(
+    mut chat: ResMut<Chat>,
+    mut message_events: EventReader<MessageEvent<Protocol, Channels>>,
+    player_id: Res<PlayerId>,
+) {
+    for MessageEvent(channel, protocol) in message_events.iter() {
+        if let (Channels::Chat, Protocol::ChatMessage(message)) = (channel, protocol) {
+            let own = Some(*message.sender_id) == **player_id;
+            if !chat.open && !own {
+                chat.unread += 1;
+            }
+
+            chat.entries.push(ChatEntry {
+                username: (*message.username).clone(),
+                text: (*message.text).clone(),
+                scope: (*message.scope).clone(),
+                own,
+            });
+        }
+    }
+}
+
+fn clear_chat(mut chat: ResMut<Chat>) {
+    *chat = Chat::default();
+}
+
+fn send_chat(client: &mut Client<Protocol, Channels>, text: &str) {
+    if let Some(text) = sanitize_chat_message(text) {
+        client.send_message(Channels::Chat, &SendChat::new(text));
+    }
+}
+
+fn chat_panel(
+    mut chat: ResMut<Chat>,
+    mut client: Client<Protocol, Channels>,
+    mut egui_context: ResMut<EguiContext>,
+    game_state: Res<State<GameState>>,
+    settings: Res<Settings>,
+    texts: Res<Texts>,
+) {
+    let language = &settings.language;
+    let scope = current_scope(&game_state);
+    let chat = &mut *chat;
+
+    let title = if chat.unread > 0 {
+        format!("💬 {} ({})", texts.get(TextId::Chat, language), chat.unread)
+    } else {
+        format!("💬 {}", texts.get(TextId::Chat, language))
+    };
+
+    egui::Area::new("ChatToggle")
+        .anchor(egui::Align2::LEFT_BOTTOM, [10.0, -10.0])
+        .show(egui_context.ctx_mut(), |ui| {
+            if ui.button(title).clicked() {
+                chat.open = !chat.open;
+            }
+        });
+
+    if !chat.open {
+        return;
+    }
+    chat.unread = 0;
+
+    egui::Window::new(texts.get(TextId::Chat, language))
+        .id(egui::Id::new("Chat"))
+        .anchor(egui::Align2::LEFT_BOTTOM, [10.0, -45.0])
+        .collapsible(false)
+        .resizable(false)
+        .default_width(300.0)
+        .show(egui_context.ctx_mut(), |ui| {
+            egui::ScrollArea::vertical()
+                .max_height(200.0)
+                .stick_to_bottom()
+                .show(ui, |ui| {
+                    for entry in chat.entries.iter().filter(|entry| entry.scope == scope) {
+                        ui.horizontal_wrapped(|ui| {
+                            let mut username = egui::RichText::new(format!("{}:", entry.username));
+                            if entry.own {
+                                username = username.color(egui::Color32::LIGHT_BLUE);
+                            }
+                            ui.label(username.strong());
+                            ui.label(&entry.text);
+                        });
+                    }
+                });
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                for emote in EMOTES {
+                    if ui.button(*emote).clicked() {
+                        send_chat(&mut client, emote);
+                    }
+                }
+            });
+
+            ui.horizontal(|ui| {
+                let response = ui.text_edit_singleline(&mut chat.draft);
+                let submitted = response.lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+
+                if ui.button(texts.get(TextId::SendChat, language)).clicked() || submitted {
+                    send_chat(&mut client, &chat.draft);
+                    chat.draft.clear();
+                    response.request_focus();
+                }
+            });
+        });
+}
@@ -0,0 +1,267 @@
+use crate::Settings;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uno::{
+    card::Color,
+    texts::{TextId, Texts},
+};
+
+pub struct ControlsPlugin;
+
+/// What a key or a gamepad button does in game
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ControlAction {
+    SelectPrevious,
+    SelectNext,
+    PlayCard,
+    DrawCard,
+    Uno,
+    CounterUno,
+    ChooseYellow,
+    ChooseRed,
+    ChooseBlue,
+    ChooseGreen,
+}
+
+impl ControlAction {
+    pub const ALL: [ControlAction; 10] = [
+        ControlAction::SelectPrevious,
+        ControlAction::SelectNext,
+        ControlAction::PlayCard,
+        ControlAction::DrawCard,
+        ControlAction::Uno,
+        ControlAction::CounterUno,
+        ControlAction::ChooseYellow,
+        ControlAction::ChooseRed,
+        ControlAction::ChooseBlue,
+        ControlAction::ChooseGreen,
+    ];
+
+    pub fn text_id(self) -> TextId {
+        match self {
+            ControlAction::SelectPrevious => TextId::PreviousCard,
+            ControlAction::SelectNext => TextId::NextCard,
+            ControlAction::PlayCard => TextId::PlaySelectedCard,
+            ControlAction::DrawCard => TextId::DrawCard,
+            ControlAction::Uno => TextId::Uno,
+            ControlAction::CounterUno => TextId::CounterUno,
+            ControlAction::ChooseYellow => TextId::Yellow,
+            ControlAction::ChooseRed => TextId::Red,
+            ControlAction::ChooseBlue => TextId::Blue,
+            ControlAction::ChooseGreen => TextId::Green,
+        }
+    }
+
+    /// Color picked by the action after a wild card
+    pub fn color(self) -> Option<Color> {
+        match self {
+            ControlAction::ChooseYellow => Some(Color::Yellow),
+            ControlAction::ChooseRed => Some(Color::Red),
+            ControlAction::ChooseBlue => Some(Color::Blue),
+            ControlAction::ChooseGreen => Some(Color::Green),
+            _ => None,
+        }
+    }
+}
+
+/// Keys and gamepad buttons bound to each action, saved with the settings
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct Bindings {
+    pub keys: HashMap<ControlAction, KeyCode>,
+    pub gamepad: HashMap<ControlAction, GamepadButtonType>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Bindings {
+            keys: HashMap::from([
+                (ControlAction::SelectPrevious, KeyCode::Left),
+                (ControlAction::SelectNext, KeyCode::Right),
+                (ControlAction::PlayCard, KeyCode::Return),
+                (ControlAction::DrawCard, KeyCode::D),
+                (ControlAction::Uno, KeyCode::U),
+                (ControlAction::CounterUno, KeyCode::C),
+                (ControlAction::ChooseYellow, KeyCode::Key1),
+                (ControlAction::ChooseRed, KeyCode::Key2),
+                (ControlAction::ChooseBlue, KeyCode::Key3),
+                (ControlAction::ChooseGreen, KeyCode::Key4),
+            ]),
+            gamepad: HashMap::from([
+                (ControlAction::SelectPrevious, GamepadButtonType::DPadLeft),
+                (ControlAction::SelectNext, GamepadButtonType::DPadRight),
+                (ControlAction::PlayCard, GamepadButtonType::South),
+                (ControlAction::DrawCard, GamepadButtonType::West),
+                (ControlAction::Uno, GamepadButtonType::North),
+                (ControlAction::CounterUno, GamepadButtonType::East),
+                (ControlAction::ChooseYellow, GamepadButtonType::LeftTrigger),
+                (ControlAction::ChooseRed, GamepadButtonType::LeftTrigger2),
+                (ControlAction::ChooseBlue, GamepadButtonType::RightTrigger),
+                (ControlAction::ChooseGreen, GamepadButtonType::RightTrigger2),
+            ]),
+        }
+    }
+}
+
+// Ressources
+#[derive(Default)]
+pub struct ControlsWindow {
+    pub open: bool,
+    /// Action waiting for the player to press its new key or button
+    remapping: Option<ControlAction>,
+}
+
+// Events
+#[derive(Deref, DerefMut)]
+pub struct ControlEvent(pub ControlAction);
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ControlsWindow>()
+            .add_event::<ControlEvent>()
+            .add_system_to_stage(CoreStage::PreUpdate, read_controls)
+            .add_system(remap_control)
+            .add_system_to_stage(CoreStage::PostUpdate, controls_window);
+    }
+}
+
+fn read_controls(
+    mut egui_context: ResMut<EguiContext>,
+    mut control_events: EventWriter<ControlEvent>,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    controls_window: Res<ControlsWindow>,
+    settings: Res<Settings>,
+) {
+    if controls_window.remapping.is_some() {
+        return;
+    }
+
+    // Keys typed in a text field, like the chat, aren't meant for the game
+    let keyboard_free = !egui_context.ctx_mut().wants_keyboard_input();
+
+    for action in ControlAction::ALL {
+        let key_pressed = keyboard_free
+            && settings
+                .bindings
+                .keys
+                .get(&action)
+                .map_or(false, |key| keys.just_pressed(*key));
+
+        let button_pressed = settings
+            .bindings
+            .gamepad
+            .get(&action)
+            .map_or(false, |button_type| {
+                gamepads
+                    .iter()
+                    .any(|gamepad| buttons.just_pressed(GamepadButton(*gamepad, *button_type)))
+            });
+
+        if key_pressed || button_pressed {
+            control_events.send(ControlEvent(action));
+        }
+    }
+}
+
+/// Bind the next key or button pressed to the action being remapped
+fn remap_control(
+    mut controls_window: ResMut<ControlsWindow>,
+    mut settings: ResMut<Settings>,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+) {
+    let action = match controls_window.remapping {
+        Some(action) => action,
+        None => return,
+    };
+
+    if keys.just_pressed(KeyCode::Escape) {
+        controls_window.remapping = None;
+    } else if let Some(key) = keys.get_just_pressed().next() {
+        settings.bindings.keys.insert(action, *key);
+        controls_window.remapping = None;
+    } else if let Some(GamepadButton(_, button_type)) = buttons.get_just_pressed().next() {
+        settings.bindings.gamepad.insert(action, *button_type);
+        controls_window.remapping = None;
+    }
+}
+
+fn controls_window(
+    mut egui_context: ResMut<EguiContext>,
+    mut controls_window: ResMut<ControlsWindow>,
+    mut settings: ResMut<Settings>,
+    texts: Res<Texts>,
+) {
+    if !controls_window.open {
+        return;
+    }
+
+    let language = &settings.language.clone();
+    let mut open = true;
+
+    egui::Window::new(texts.get(TextId::Controls, language))
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .collapsible(false)
+        .resizable(false)
+        .open(&mut open)
+        .show(egui_context.ctx_mut(), |ui| {
+            egui::Grid::new("Bindings")
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("");
+                    ui.strong(texts.get(TextId::Keyboard, language));
+                    ui.strong(texts.get(TextId::Gamepad, language));
+                    ui.end_row();
+
+                    for action in ControlAction::ALL {
+                        ui.label(texts.get(action.text_id(), language));
+
+                        if controls_window.remapping == Some(action) {
+                            ui.label(texts.get(TextId::PressKey, language));
+                            ui.label("");
+                        } else {
+                            let key = settings.bindings.keys.get(&action);
+                            let button = settings.bindings.gamepad.get(&action);
+
+                            let key_clicked = ui
+                                .button(
+                                    key.map_or_else(|| "-".to_owned(), |key| format!("{key:?}")),
+                                )
+                                .clicked();
+                            let button_clicked =
+                                ui.button(button.map_or_else(
+                                    || "-".to_owned(),
+                                    |button| format!("{button:?}"),
+                                ))
+                                .clicked();
+
+                            if key_clicked || button_clicked {
+                                controls_window.remapping = Some(action);
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+
+            ui.separator();
+
+            ui.vertical_centered(|ui| {
+                if ui
+                    .button(texts.get(TextId::ResetControls, language))
+                    .clicked()
+                {
+                    settings.bindings = Bindings::default();
+                    controls_window.remapping = None;
+                }
+            });
+        });
+
+    if !open {
+        *controls_window = ControlsWindow::default();
+    }
+}
@@ -1,22 +1,65 @@
 use super::{
-    run_if_in_game, ChooseColor, ColorChosenEvent, GameAssets, PlayedCardValidationEvent,
-    ToBeRemoved,
+    hand_layout::{HandLayout, HandScroll, HoveredCard},
+    run_if_in_game,
+    seats::ActiveSeat,
+    selection::HandSelection,
+    snapshot::LastSnapshot,
+    themes::{CardBack, CardThemes},
+    ChooseColor, ColorChosenEvent, GameActionEvent, PlayedCardValidationEvent, ToBeRemoved,
 };
 use crate::{
     utils::constants::{
         BASE_CARD_Z, CARD_ANIMATION_TIME_S, CARD_DROP_ZONE, CARD_HEIGHT, CARD_SCALE, CARD_WIDTH,
-        DECK_POS, DISCARD_POS, DISCARD_Z_INCREASE, Z_INCREASE,
+        DECK_POS, DIMMED_CARD_COLOR, DISCARD_POS, DISCARD_Z_INCREASE, HOVERED_CARD_RAISE,
+        PLAYABLE_CARD_RAISE, SELECTED_CARD_RAISE, Z_INCREASE,
     },
-    Draggable, Dragged, Dropped, GameState, SpriteSize,
+    Draggable, Dragged, Dropped, GameState, PlayerId, Settings, SpriteSize,
 };
 use bevy::{prelude::*, window::WindowResized};
-use naia_bevy_client::Client;
+use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use uno::{
     card::{Card, Color, Value},
-    network::{protocol, Channels, Protocol},
+    network::protocol::{CurrentColor, GameAction, Player},
+    texts::TextId,
 };
 
+/// Order of the cards in the hand
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HandSort {
+    /// Order in which the cards were drawn, or the one chosen by dragging them
+    Manual,
+    Color,
+    Value,
+}
+
+impl HandSort {
+    pub const ALL: [HandSort; 3] = [HandSort::Manual, HandSort::Color, HandSort::Value];
+
+    pub fn text_id(self) -> TextId {
+        match self {
+            HandSort::Manual => TextId::SortManual,
+            HandSort::Color => TextId::SortColor,
+            HandSort::Value => TextId::SortValue,
+        }
+    }
+
+    /// Cards are sorted by this key, cards with the same key stay in the same order
+    fn key(self, card: &Card) -> (u8, u8) {
+        // 0 is the last value of the enum, but it goes before 1 in a hand
+        let value = match card.value {
+            Value::Zero => 0,
+            value => value as u8,
+        };
+
+        match self {
+            HandSort::Manual => (0, 0),
+            HandSort::Color => (card.color as u8, value),
+            HandSort::Value => (value, card.color as u8),
+        }
+    }
+}
+
 // Ressources
 pub struct CurrentCardZ(pub f32);
 pub struct Hand {
@@ -34,17 +77,23 @@ pub struct HandItem {
 }
 #[derive(Component, Debug)]
 pub struct CardPosition(pub Vec3);
+/// Rotation of a card of the fanned hand, the other cards are straight
+#[derive(Component, Debug)]
+pub struct CardAngle(pub f32);
 #[derive(Component, Default)]
 pub struct CardAnimation {
     time: Duration,
 }
 #[derive(Component, Default)]
 pub struct CardWaitingForValidation;
+/// Card of the hand that can be played on the discard
+#[derive(Component)]
+pub struct Playable;
 
 // Events
 pub struct DrawCardEvent(pub Card);
 pub struct CardPlayedEvent(pub Card);
-struct PlayCardEvent(pub Card);
+pub struct PlayCardEvent(pub Card);
 pub struct ReorganizeHandEvent;
 
 pub struct CardsPlugin;
@@ -69,7 +118,8 @@ impl Plugin for CardsPlugin {
                     .with_system(remove_animation_on_drag)
                     .with_system(play_card)
                     .with_system(color_chosen)
-                    .with_system(played_card_validation),
+                    .with_system(played_card_validation)
+                    .with_system(highlight_playable_cards),
             )
             .add_system_set_to_stage(
                 CoreStage::PostUpdate,
@@ -80,20 +130,12 @@ impl Plugin for CardsPlugin {
     }
 }
 
-pub fn card_to_spritesheet_index(card: &uno::card::Card) -> usize {
-    match card.value {
-        Value::Back => 0,
-        Value::Wild => 1,
-        Value::WildFour => 6,
-        value => 11 + 13 * card.color as usize + value as usize,
-    }
-}
-
 fn card_played(
     mut commands: Commands,
     mut card_played_event: EventReader<CardPlayedEvent>,
     discard_query: Query<(Entity, &Transform), With<Discard>>,
-    game_assets: Res<GameAssets>,
+    card_themes: Res<CardThemes>,
+    active_seat: Res<ActiveSeat>,
 ) {
     for CardPlayedEvent(card) in card_played_event.iter() {
         let mut played_card_z = BASE_CARD_Z;
@@ -109,20 +151,21 @@ fn card_played(
                 });
         }
 
-        let mut transform = Transform::from_xyz(2000.0, DECK_POS.1, played_card_z);
+        // The card comes from the seat of the player who played it
+        let from = active_seat.0.unwrap_or(Vec2::new(2000.0, DECK_POS.1));
+        let mut transform = Transform::from_translation(from.extend(played_card_z));
         transform.scale = Vec3::new(CARD_SCALE, CARD_SCALE, 1.0);
+        let (sprite, texture_atlas) = card_themes.sprite(card);
 
         commands
             .spawn_bundle(SpriteSheetBundle {
-                sprite: TextureAtlasSprite {
-                    index: card_to_spritesheet_index(card),
-                    ..TextureAtlasSprite::default()
-                },
-                texture_atlas: game_assets.cards.clone_weak(),
+                sprite,
+                texture_atlas,
                 transform,
                 ..SpriteSheetBundle::default()
             })
             .insert(Discard)
+            .insert(CardComponent(*card))
             .insert(CardAnimation::default())
             .insert(CardPosition(Vec3::new(
                 DISCARD_POS.0,
@@ -132,39 +175,33 @@ fn card_played(
     }
 }
 
-fn setup_cards(mut commands: Commands, game_assets: Res<GameAssets>) {
+fn setup_cards(mut commands: Commands, card_themes: Res<CardThemes>) {
     let mut transform = Transform::from_xyz(DECK_POS.0, DECK_POS.1, 0.1);
     transform.scale = Vec3::new(CARD_SCALE, CARD_SCALE, 1.0);
 
-    let card_back = Card {
-        value: Value::Back,
-        color: Color::Black,
-    };
+    let (sprite, texture_atlas) = card_themes.sprite(&Card::back());
 
     // Back of card representing the deck
-    commands.spawn_bundle(SpriteSheetBundle {
-        sprite: TextureAtlasSprite {
-            index: card_to_spritesheet_index(&card_back),
-            ..TextureAtlasSprite::default()
-        },
-        texture_atlas: game_assets.cards.clone_weak(),
-        transform,
-        ..SpriteSheetBundle::default()
-    });
+    commands
+        .spawn_bundle(SpriteSheetBundle {
+            sprite: sprite.clone(),
+            texture_atlas: texture_atlas.clone(),
+            transform,
+            ..SpriteSheetBundle::default()
+        })
+        .insert(CardBack);
 
     // Discard for the cards
     transform.translation = Vec3::new(DISCARD_POS.0, DISCARD_POS.1, BASE_CARD_Z - 8.0 * Z_INCREASE);
     commands
         .spawn_bundle(SpriteSheetBundle {
-            sprite: TextureAtlasSprite {
-                index: card_to_spritesheet_index(&card_back),
-                ..TextureAtlasSprite::default()
-            },
-            texture_atlas: game_assets.cards.clone_weak(),
+            sprite,
+            texture_atlas,
             transform,
             ..SpriteSheetBundle::default()
         })
-        .insert(Discard);
+        .insert(Discard)
+        .insert(CardBack);
 }
 
 fn draw_card(
@@ -173,21 +210,18 @@ fn draw_card(
     mut reorganize_hand_event: EventWriter<ReorganizeHandEvent>,
     mut current_card_z: ResMut<CurrentCardZ>,
     mut hand: ResMut<Hand>,
-    game_assets: Res<GameAssets>,
+    card_themes: Res<CardThemes>,
 ) {
     for DrawCardEvent(card) in draw_card_event.iter() {
-        let index = card_to_spritesheet_index(card);
+        let (sprite, texture_atlas) = card_themes.sprite(card);
 
         let mut card_transform = Transform::from_xyz(DECK_POS.0, DECK_POS.1, current_card_z.0);
         card_transform.scale = Vec3::new(CARD_SCALE, CARD_SCALE, 1.0);
 
         commands
             .spawn_bundle(SpriteSheetBundle {
-                sprite: TextureAtlasSprite {
-                    index,
-                    ..TextureAtlasSprite::default()
-                },
-                texture_atlas: game_assets.cards.clone_weak(),
+                sprite,
+                texture_atlas,
                 transform: card_transform,
                 ..SpriteSheetBundle::default()
             })
@@ -211,35 +245,68 @@ fn reorganize_hand(
     mut reorganize_hand_event: EventReader<ReorganizeHandEvent>,
     windows: Res<Windows>,
     hand: Res<Hand>,
-    mut query: Query<(Entity, &mut HandItem, &mut CardPosition)>,
+    selection: Res<HandSelection>,
+    hovered: Res<HoveredCard>,
+    mut scroll: ResMut<HandScroll>,
+    settings: Res<Settings>,
+    mut query: Query<(
+        Entity,
+        &CardComponent,
+        &mut HandItem,
+        &mut CardPosition,
+        &mut Transform,
+        Option<&Playable>,
+    )>,
 ) {
     if hand.size == 0 {
         return;
     }
 
     for _ in reorganize_hand_event.iter() {
-        let window = windows.get_primary().unwrap();
-        let card_y = -window.height() / 2.0 + (CARD_HEIGHT * CARD_SCALE) * 0.25;
-
-        const X_PADDING: f32 = 0.1;
-        let width = window.width() * (1.0 - 2.0 * X_PADDING);
-        let part = width / hand.size as f32;
+        // Sorting also removes the holes left by the cards that aren't in the hand anymore
+        let mut items = query.iter_mut().collect::<Vec<_>>();
+        items.sort_by_key(|(_, card, item, _, _, _)| (settings.hand_sort.key(&card.0), item.index));
 
-        for (entity, item, mut card_position) in query.iter_mut() {
-            card_position.0.y = card_y;
-            card_position.0.x = part * item.index as f32 + part / 2.0 - width / 2.0;
-
-            commands.entity(entity).insert(CardAnimation::default());
+        let mut layout = HandLayout::new(windows.get_primary().unwrap(), items.len(), scroll.first);
+        if let Some(selected) = selection.0 {
+            layout.show(selected);
         }
+        scroll.first = layout.first;
+        scroll.scrollable = layout.scrollable();
 
-        // Check if any index is missing, to not create a hole in the player's hand
-        if let Some(max_index) = query.iter().map(|(_, item, _)| item.index).max() {
-            if max_index >= hand.size {
-                for (idx, (_, mut item, mut card_position)) in query.iter_mut().enumerate() {
-                    item.index = idx;
-                    card_position.0.z = BASE_CARD_Z + idx as f32 * Z_INCREASE;
-                }
-            }
+        for (index, (entity, _, mut item, mut card_position, mut transform, playable)) in
+            items.into_iter().enumerate()
+        {
+            item.index = index;
+
+            let (position, angle) = layout.placement(index);
+            let raise = if selection.0 == Some(index) {
+                SELECTED_CARD_RAISE
+            } else if hovered.0 == Some(index) {
+                HOVERED_CARD_RAISE
+            } else if playable.is_some() {
+                PLAYABLE_CARD_RAISE
+            } else {
+                0.0
+            };
+            // The card under the cursor is drawn above its neighbours
+            let z_index = if hovered.0 == Some(index) {
+                hand.size.max(index) + 1
+            } else {
+                index
+            };
+
+            card_position.0 = Vec3::new(
+                position.x,
+                position.y + raise,
+                BASE_CARD_Z + z_index as f32 * Z_INCREASE,
+            );
+            transform.scale = Vec3::new(layout.scale, layout.scale, 1.0);
+
+            commands
+                .entity(entity)
+                .insert(CardAngle(angle))
+                .insert(CardAnimation::default());
         }
     }
 }
@@ -247,9 +314,17 @@ fn reorganize_hand(
 fn card_dropped(
     mut commands: Commands,
     mut play_card_event: EventWriter<PlayCardEvent>,
-    query: Query<(Entity, &Transform, &CardComponent), With<Dropped>>,
+    mut reorganize_hand_event: EventWriter<ReorganizeHandEvent>,
+    windows: Res<Windows>,
+    hand: Res<Hand>,
+    scroll: Res<HandScroll>,
+    mut settings: ResMut<Settings>,
+    mut query: Query<(Entity, &Transform, &CardComponent, &mut HandItem), With<Dropped>>,
+    mut hand_query: Query<&mut HandItem, Without<Dropped>>,
 ) {
-    for (entity, transform, card) in query.iter() {
+    for (entity, transform, card, mut item) in query.iter_mut() {
+        let layout = HandLayout::new(windows.get_primary().unwrap(), hand.size, scroll.first);
+
         if transform.translation.x < DISCARD_POS.0 + CARD_DROP_ZONE
             && transform.translation.x > DISCARD_POS.0 - CARD_DROP_ZONE
             && transform.translation.y < DISCARD_POS.1 + CARD_DROP_ZONE
@@ -262,6 +337,33 @@ fn card_dropped(
 
             play_card_event.send(PlayCardEvent(card.0))
         } else {
+            // Dropped between two other cards of the hand, the card is moved there
+            if layout.contains(transform.translation.y) {
+                let old_index = item.index;
+                let new_index = layout.index_at(transform.translation.x);
+
+                for mut other_item in hand_query.iter_mut() {
+                    if old_index < new_index
+                        && other_item.index > old_index
+                        && other_item.index <= new_index
+                    {
+                        other_item.index -= 1;
+                    } else if new_index < old_index
+                        && other_item.index >= new_index
+                        && other_item.index < old_index
+                    {
+                        other_item.index += 1;
+                    }
+                }
+                item.index = new_index;
+
+                // The order of the player replaces the sorted one
+                if settings.hand_sort != HandSort::Manual {
+                    settings.hand_sort = HandSort::Manual;
+                }
+                reorganize_hand_event.send(ReorganizeHandEvent);
+            }
+
             commands
                 .entity(entity)
                 .remove::<Dropped>()
@@ -270,33 +372,105 @@ fn card_dropped(
     }
 }
 
+/// During the turn of the player, lift the cards that can be played on the discard and dim the
+/// other ones
+fn highlight_playable_cards(
+    mut commands: Commands,
+    mut reorganize_hand_event: EventWriter<ReorganizeHandEvent>,
+    discard_query: Query<&CardComponent, With<Discard>>,
+    current_color_query: Query<&CurrentColor>,
+    players_query: Query<&Player>,
+    last_snapshot: Res<LastSnapshot>,
+    player_id: Res<PlayerId>,
+    mut hand_query: Query<
+        (
+            Entity,
+            &CardComponent,
+            &mut TextureAtlasSprite,
+            Option<&Playable>,
+        ),
+        With<HandItem>,
+    >,
+) {
+    let playing = players_query
+        .iter()
+        .any(|player| Some(*player.id) == **player_id && *player.is_playing);
+    let top_card = discard_query.iter().next().map(|card| card.0);
+    let current_color = match (current_color_query.iter().next(), &last_snapshot.0) {
+        (Some(CurrentColor { color }), _) => (**color).into(),
+        (None, Some(snapshot)) => snapshot.current_color,
+        (None, None) => Color::Black,
+    };
+
+    let mut changed = false;
+
+    for (entity, card, mut sprite, playable) in hand_query.iter_mut() {
+        let can_be_played = playing
+            && top_card.map_or(false, |top_card| {
+                card.0.can_be_played(top_card, current_color)
+            });
+        let color = if playing && !can_be_played {
+            DIMMED_CARD_COLOR
+        } else {
+            bevy::prelude::Color::WHITE
+        };
+
+        if sprite.color != color {
+            sprite.color = color;
+        }
+
+        if can_be_played != playable.is_some() {
+            if can_be_played {
+                commands.entity(entity).insert(Playable);
+            } else {
+                commands.entity(entity).remove::<Playable>();
+            }
+            changed = true;
+        }
+    }
+
+    if changed {
+        reorganize_hand_event.send(ReorganizeHandEvent);
+    }
+}
+
 fn play_card(
     mut play_card_event: EventReader<PlayCardEvent>,
-    mut client: Client<Protocol, Channels>,
+    mut game_action_event: EventWriter<GameActionEvent>,
 ) {
     for PlayCardEvent(card) in play_card_event.iter() {
-        client.send_message(Channels::Uno, &protocol::CardPlayed::new(*card));
+        game_action_event.send(GameActionEvent(GameAction::play_card(*card)));
     }
 }
 
 fn animate_card(
     time: Res<Time>,
     mut commands: Commands,
-    mut query: Query<(Entity, &CardPosition, &mut Transform, &mut CardAnimation), Without<Dragged>>,
+    mut query: Query<
+        (
+            Entity,
+            &CardPosition,
+            Option<&CardAngle>,
+            &mut Transform,
+            &mut CardAnimation,
+        ),
+        Without<Dragged>,
+    >,
 ) {
-    for (entity, card_position, mut transform, mut card_animation) in query.iter_mut() {
+    for (entity, card_position, angle, mut transform, mut card_animation) in query.iter_mut() {
         card_animation.time += time.delta();
+        let rotation = Quat::from_rotation_z(angle.map_or(0.0, |angle| angle.0));
 
         if card_animation.time.as_secs_f32() >= CARD_ANIMATION_TIME_S {
             transform.translation = card_position.0;
+            transform.rotation = rotation;
             commands.entity(entity).remove::<CardAnimation>();
             return;
         }
 
-        transform.translation = transform.translation.lerp(
-            card_position.0,
-            ease_out_sine(card_animation.time.as_secs_f32() / CARD_ANIMATION_TIME_S),
-        );
+        let progress = ease_out_sine(card_animation.time.as_secs_f32() / CARD_ANIMATION_TIME_S);
+        transform.translation = transform.translation.lerp(card_position.0, progress);
+        transform.rotation = transform.rotation.slerp(rotation, progress);
         // transform.translation.z = card_position.0.z;
     }
 }
@@ -320,11 +494,11 @@ fn remove_animation_on_drag(
 }
 
 pub fn color_chosen(
-    mut client: Client<Protocol, Channels>,
+    mut game_action_event: EventWriter<GameActionEvent>,
     mut color_chosen_event: EventReader<ColorChosenEvent>,
 ) {
     for ColorChosenEvent(color) in color_chosen_event.iter() {
-        client.send_message(Channels::Uno, &protocol::ColorChosen::new(*color));
+        game_action_event.send(GameActionEvent(GameAction::choose_color(*color)));
     }
 }
 
@@ -378,12 +552,14 @@ pub fn played_card_validation(
             // Set the position of the played card to be on top of the discard
             card_pos.0 = Vec3::new(DISCARD_POS.0, DISCARD_POS.1, played_card_z);
             played_card_transform.translation.z = played_card_z;
+            played_card_transform.scale = Vec3::new(CARD_SCALE, CARD_SCALE, 1.0);
 
             // Remove the ability to drag the new card that is now on top of the discard
             commands
                 .entity(entity)
                 .remove::<Draggable>()
                 .remove::<HandItem>()
+                .remove::<CardAngle>()
                 .insert(Discard);
 
             hand.size -= 1;
@@ -0,0 +1,240 @@
+use super::run_if_in_game;
+use crate::{
+    offline::{OfflineEntity, OfflineGame},
+    utils::constants::MAX_FEED_ENTRIES,
+    GameState, PlayerId, Settings,
+};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use naia_bevy_client::events::MessageEvent;
+use uno::{
+    card::{Card, Color, Value},
+    network::{
+        protocol::{DrawReason, GameLogKind, Player},
+        Channels, Protocol,
+    },
+    texts::{Language, TextId, Texts},
+};
+
+pub struct FeedPlugin;
+
+enum FeedAction {
+    Game(GameLogKind),
+    Drew(u32, DrawReason),
+}
+
+struct FeedEntry {
+    player: String,
+    target: Option<String>,
+    action: FeedAction,
+    /// The player of the client is involved
+    own: bool,
+}
+
+// Ressources
+/// What the players did since the start of the game, newest last
+#[derive(Default)]
+pub struct ActionFeed {
+    entries: Vec<FeedEntry>,
+}
+
+impl Plugin for FeedPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActionFeed>()
+            .add_system_set(SystemSet::on_enter(GameState::Game).with_system(clear_feed))
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(run_if_in_game)
+                    .with_system(receive_game_logs)
+                    .with_system(feed_panel),
+            );
+    }
+}
+
+fn card_name(card: Card, texts: &Texts, language: &Language) -> String {
+    let value = match card.value {
+        Value::Skip => texts.get(TextId::Skip, language),
+        Value::Reverse => texts.get(TextId::Reverse, language),
+        Value::DrawTwo => "+2".to_owned(),
+        Value::Wild => return texts.get(TextId::Wild, language),
+        Value::WildFour => return texts.get(TextId::WildFour, language),
+        Value::Zero => "0".to_owned(),
+        value => (value as u8).to_string(),
+    };
+
+    match color_name(card.color, texts, language) {
+        Some(color) => texts.format(
+            TextId::CardName,
+            language,
+            &[("color", &color), ("value", &value)],
+        ),
+        None => value,
+    }
+}
+
+/// Short explanation of cards drawn because of a penalty
+pub fn draw_reason_name(reason: &DrawReason, texts: &Texts, language: &Language) -> Option<String> {
+    match reason {
+        DrawReason::DrawTwo => Some("+2".to_owned()),
+        DrawReason::WildFour => Some("+4".to_owned()),
+        DrawReason::CounterUno => Some(texts.get(TextId::CounterUno, language)),
+        DrawReason::Timeout => Some(texts.get(TextId::TurnTimeout, language)),
+        DrawReason::Deal | DrawReason::Voluntary => None,
+    }
+}
+
+fn color_name(color: Color, texts: &Texts, language: &Language) -> Option<String> {
+    let text_id = match color {
+        Color::Yellow => TextId::Yellow,
+        Color::Red => TextId::Red,
+        Color::Blue => TextId::Blue,
+        Color::Green => TextId::Green,
+        Color::Black => return None,
+    };
+
+    Some(texts.get(text_id, language))
+}
+
+impl FeedEntry {
+    fn text(&self, texts: &Texts, language: &Language) -> String {
+        let player = self.player.as_str();
+        let target = self.target.as_deref().unwrap_or_default();
+
+        let kind = match &self.action {
+            FeedAction::Game(kind) => kind,
+            FeedAction::Drew(count, reason) => {
+                let count = count.to_string();
+
+                return match draw_reason_name(reason, texts, language) {
+                    Some(reason) => texts.format(
+                        TextId::LogDrewPenalty,
+                        language,
+                        &[("player", player), ("count", &count), ("reason", &reason)],
+                    ),
+                    None => texts.format(
+                        TextId::LogDrewCards,
+                        language,
+                        &[("player", player), ("count", &count)],
+                    ),
+                };
+            }
+        };
+
+        match kind {
+            GameLogKind::PlayedCard { card } => {
+                let card = Card::from(card);
+                let card_name = card_name(card, texts, language);
+                let text_id = match card.value {
+                    // The next player draws and is skipped, the draw has its own entry
+                    Value::Skip | Value::DrawTwo | Value::WildFour => TextId::LogSkipped,
+                    Value::Reverse => TextId::LogReversed,
+                    _ => TextId::LogPlayedCard,
+                };
+
+                texts.format(
+                    text_id,
+                    language,
+                    &[("player", player), ("card", &card_name), ("target", target)],
+                )
+            }
+            GameLogKind::ChoseColor { color } => texts.format(
+                TextId::LogChoseColor,
+                language,
+                &[
+                    ("player", player),
+                    (
+                        "color",
+                        &color_name((*color).into(), texts, language).unwrap_or_default(),
+                    ),
+                ],
+            ),
+            GameLogKind::Uno => texts.format(TextId::LogUno, language, &[("player", player)]),
+            GameLogKind::CounterUno => texts.format(
+                TextId::LogCounterUno,
+                language,
+                &[("player", player), ("target", target)],
+            ),
+        }
+    }
+}
+
+fn clear_feed(mut action_feed: ResMut<ActionFeed>) {
+    *action_feed = ActionFeed::default();
+}
+
+/// The names are kept with the entries, so that the players leaving the game stay in the feed
+fn receive_game_logs(
+    mut action_feed: ResMut<ActionFeed>,
+    mut message_events: EventReader<MessageEvent<Protocol, Channels>>,
+    players_query: Query<(&Player, Option<&OfflineEntity>)>,
+    offline_game: Option<Res<OfflineGame>>,
+    player_id: Res<PlayerId>,
+) {
+    let username = |id: u64| {
+        players_query
+            .iter()
+            .filter(|(_, offline)| offline.is_some() == offline_game.is_some())
+            .find(|(player, _)| *player.id == id)
+            .map(|(player, _)| (*player.username).clone())
+            .unwrap_or_else(|| "?".to_owned())
+    };
+
+    for MessageEvent(_, message) in message_events.iter() {
+        match message {
+            Protocol::GameLog(game_log) => {
+                let own = player_id.is_some()
+                    && (**player_id == Some(*game_log.player_id)
+                        || **player_id == *game_log.target_id);
+
+                action_feed.entries.push(FeedEntry {
+                    player: username(*game_log.player_id),
+                    target: (*game_log.target_id).map(&username),
+                    action: FeedAction::Game((*game_log.kind).clone()),
+                    own,
+                });
+            }
+            // Everyone gets their cards at the start, it isn't worth an entry
+            Protocol::PlayerDrew(player_drew) if *player_drew.reason != DrawReason::Deal => {
+                action_feed.entries.push(FeedEntry {
+                    player: username(*player_drew.player_id),
+                    target: None,
+                    action: FeedAction::Drew(*player_drew.count, (*player_drew.reason).clone()),
+                    own: **player_id == Some(*player_drew.player_id),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    let overflow = action_feed.entries.len().saturating_sub(MAX_FEED_ENTRIES);
+    action_feed.entries.drain(..overflow);
+}
+
+fn feed_panel(
+    action_feed: Res<ActionFeed>,
+    mut egui_context: ResMut<EguiContext>,
+    settings: Res<Settings>,
+    texts: Res<Texts>,
+) {
+    let language = &settings.language;
+
+    egui::Window::new(texts.get(TextId::ActionFeed, language))
+        .id(egui::Id::new("ActionFeed"))
+        .anchor(egui::Align2::RIGHT_TOP, [-10.0, 60.0])
+        .resizable(false)
+        .default_width(280.0)
+        .show(egui_context.ctx_mut(), |ui| {
+            egui::ScrollArea::vertical()
+                .max_height(160.0)
+                .stick_to_bottom()
+                .show(ui, |ui| {
+                    for entry in &action_feed.entries {
+                        let mut text = egui::RichText::new(entry.text(&texts, language));
+                        if entry.own {
+                            text = text.color(egui::Color32::LIGHT_BLUE);
+                        }
+                        ui.label(text);
+                    }
+                });
+        });
+}
@@ -0,0 +1,215 @@
+use super::{
+    cards::{HandItem, ReorganizeHandEvent},
+    run_if_in_game,
+};
+use crate::{
+    utils::{
+        constants::{
+            CARD_HEIGHT, CARD_SCALE, CARD_WIDTH, FAN_ANGLE, FAN_MAX_ANGLE_STEP, FAN_RADIUS,
+            HAND_HEIGHT_RATIO, MAX_VISIBLE_CARDS, MIN_VISIBLE_CARD_PART,
+        },
+        cursor_state::CursorState,
+    },
+    Draggable, Dragged, GameState, SpriteSize,
+};
+use bevy::{input::mouse::MouseWheel, prelude::*};
+use bevy_egui::EguiContext;
+
+// Ressources
+/// First card shown when the hand has too many cards to be shown at once
+#[derive(Default)]
+pub struct HandScroll {
+    pub first: usize,
+    /// Whether the hand doesn't fit in the window and has to be scrolled
+    pub scrollable: bool,
+}
+/// Index of the card of the hand under the cursor
+#[derive(Default)]
+pub struct HoveredCard(pub Option<usize>);
+
+pub struct HandLayoutPlugin;
+
+impl Plugin for HandLayoutPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HandScroll>()
+            .init_resource::<HoveredCard>()
+            .add_system_set(SystemSet::on_exit(GameState::Game).with_system(reset_hand_layout))
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(run_if_in_game)
+                    .with_system(hover_card)
+                    .with_system(scroll_hand),
+            );
+    }
+}
+
+/// Where the cards of the hand go in the window. Cards are spread on an arc, overlapping more
+/// as the hand grows, and laid flat and scrolled once they can't all be shown
+pub struct HandLayout {
+    /// Center of the cards at the middle of the hand
+    y: f32,
+    /// Width in which the centers of the cards are spread
+    width: f32,
+    spacing: f32,
+    pub scale: f32,
+    hand_size: usize,
+    /// Number of cards shown at once, the other ones are stacked on the sides
+    visible: usize,
+    pub first: usize,
+}
+
+impl HandLayout {
+    pub fn new(window: &Window, hand_size: usize, first: usize) -> HandLayout {
+        const X_PADDING: f32 = 0.1;
+
+        // Cards shrink on small windows so that the hand doesn't hide the table
+        let scale = CARD_SCALE.min(window.height() * HAND_HEIGHT_RATIO / CARD_HEIGHT);
+        let card_width = CARD_WIDTH * scale;
+        let width = (window.width() * (1.0 - 2.0 * X_PADDING) - card_width).max(0.0);
+
+        let min_spacing = card_width * MIN_VISIBLE_CARD_PART;
+        let capacity = (width / min_spacing) as usize + 1;
+        let visible = hand_size.min(capacity).min(MAX_VISIBLE_CARDS).max(1);
+
+        let spacing = if visible > 1 {
+            (width / (visible - 1) as f32).min(card_width)
+        } else {
+            0.0
+        };
+
+        HandLayout {
+            y: -window.height() / 2.0 + (CARD_HEIGHT * scale) * 0.25,
+            width,
+            spacing,
+            scale,
+            hand_size,
+            visible,
+            first: first.min(hand_size - visible.min(hand_size)),
+        }
+    }
+
+    pub fn scrollable(&self) -> bool {
+        self.visible < self.hand_size
+    }
+
+    /// Scroll so that the card at `index` is shown
+    pub fn show(&mut self, index: usize) {
+        if index < self.first {
+            self.first = index;
+        } else if index >= self.first + self.visible {
+            self.first = index + 1 - self.visible;
+        }
+    }
+
+    /// Center and angle of the card at `index`
+    pub fn placement(&self, index: usize) -> (Vec2, f32) {
+        if self.scrollable() {
+            // Hidden cards are stacked under the first and the last visible ones
+            let slot = index.clamp(self.first, self.first + self.visible - 1) - self.first;
+            let x = slot as f32 * self.spacing - (self.visible - 1) as f32 * self.spacing / 2.0;
+
+            return (Vec2::new(x, self.y), 0.0);
+        }
+
+        let offset = index as f32 - (self.hand_size - 1) as f32 / 2.0;
+        let angle_step = (FAN_ANGLE / self.hand_size.max(2) as f32).min(FAN_MAX_ANGLE_STEP);
+        let angle = -offset * angle_step;
+        let drop = FAN_RADIUS * self.scale * (1.0 - angle.cos());
+
+        (Vec2::new(offset * self.spacing, self.y - drop), angle)
+    }
+
+    /// Index of the slot of the hand under `x`
+    pub fn index_at(&self, x: f32) -> usize {
+        if self.hand_size == 0 {
+            return 0;
+        }
+
+        let (first, count) = if self.scrollable() {
+            (self.first, self.visible)
+        } else {
+            (0, self.hand_size)
+        };
+        let slot = if self.spacing > 0.0 {
+            (x / self.spacing + (count - 1) as f32 / 2.0)
+                .round()
+                .max(0.0) as usize
+        } else {
+            0
+        };
+
+        first + slot.min(count - 1)
+    }
+
+    /// Whether a card at `y` is in the hand
+    pub fn contains(&self, y: f32) -> bool {
+        y < self.y + CARD_HEIGHT * self.scale / 2.0
+    }
+}
+
+fn reset_hand_layout(mut scroll: ResMut<HandScroll>, mut hovered: ResMut<HoveredCard>) {
+    *scroll = HandScroll::default();
+    hovered.0 = None;
+}
+
+/// Raise the card under the cursor, so that cards hidden by their neighbours can be seen
+fn hover_card(
+    mut egui_context: ResMut<EguiContext>,
+    mut hovered: ResMut<HoveredCard>,
+    mut reorganize_hand_event: EventWriter<ReorganizeHandEvent>,
+    cursor_state: Res<CursorState>,
+    hand_query: Query<(&HandItem, &Transform, &SpriteSize), With<Draggable>>,
+    dragged_query: Query<(), With<Dragged>>,
+) {
+    let cursor = cursor_state.cursor_world;
+    let new_hovered = if dragged_query.is_empty() && !egui_context.ctx_mut().wants_pointer_input() {
+        hand_query
+            .iter()
+            .filter(|(_, transform, size)| {
+                let half_size =
+                    Vec2::new(size.width, size.height) * transform.scale.truncate() / 2.0;
+                let position = transform.translation.truncate();
+
+                (cursor - position).abs().cmple(half_size).all()
+            })
+            .max_by(|(_, t1, _), (_, t2, _)| {
+                t1.translation
+                    .z
+                    .partial_cmp(&t2.translation.z)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .map(|(item, _, _)| item.index)
+    } else {
+        hovered.0
+    };
+
+    if new_hovered != hovered.0 {
+        hovered.0 = new_hovered;
+        reorganize_hand_event.send(ReorganizeHandEvent);
+    }
+}
+
+fn scroll_hand(
+    mut egui_context: ResMut<EguiContext>,
+    mut mouse_wheel_events: EventReader<MouseWheel>,
+    mut scroll: ResMut<HandScroll>,
+    mut reorganize_hand_event: EventWriter<ReorganizeHandEvent>,
+) {
+    let delta: f32 = mouse_wheel_events
+        .iter()
+        .map(|event| event.y + event.x)
+        .sum();
+
+    // Scrolling the chat or a list doesn't scroll the hand
+    if !scroll.scrollable || delta == 0.0 || egui_context.ctx_mut().wants_pointer_input() {
+        return;
+    }
+
+    if delta > 0.0 {
+        scroll.first = scroll.first.saturating_sub(1);
+    } else {
+        // Clamped by the layout
+        scroll.first += 1;
+    }
+    reorganize_hand_event.send(ReorganizeHandEvent);
+}
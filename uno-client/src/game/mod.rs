@@ -1,20 +1,33 @@
-use crate::{
-    menu::LobbyState,
-    utils::constants::{CARD_HEIGHT, CARD_PADDING, CARD_WIDTH},
-    GameState,
-};
+use crate::{menu::LobbyState, offline::OfflineGame, GameState};
 use bevy::{ecs::schedule::ShouldRun, prelude::*};
 use cards::*;
-use naia_bevy_client::events::MessageEvent;
+use naia_bevy_client::{events::MessageEvent, Client};
+use seats::SeatCard;
+use snapshot::GameSnapshotEvent;
 use uno::{
     card::Color,
-    network::{Channels, Protocol},
+    network::{
+        protocol::{DrawReason, GameAction, RoundResult},
+        Channels, Protocol,
+    },
     Player as UnoPlayer,
 };
 
 mod cards;
+mod feed;
+mod hand_layout;
+mod seats;
+mod selection;
+mod snapshot;
+mod table;
+mod themes;
 mod ui;
 
+pub use cards::{
+    CardComponent, CardPlayedEvent, DrawCardEvent, Hand, HandItem, HandSort, PlayCardEvent,
+};
+pub use themes::{CardThemes, DEFAULT_THEME};
+
 pub struct GamePlugin;
 
 // Components
@@ -34,9 +47,9 @@ pub struct ToBeRemoved {
 }
 
 // Ressources
-pub struct GameAssets {
-    cards: Handle<TextureAtlas>,
-}
+/// Results of the last game, shown on the end screen
+#[derive(Default)]
+pub struct RoundResults(pub Vec<RoundResult>);
 
 // Events
 pub struct StartGameEvent;
@@ -48,10 +61,26 @@ pub struct GameEndEvent;
 #[derive(Deref, DerefMut)]
 pub struct ExtraMessageEvent(pub Protocol);
 pub struct GameExitEvent;
+/// Cards drawn by any player of the game, the cards themselves only come to the player drawing
+pub struct PlayerDrewEvent {
+    pub player_id: u64,
+    pub count: u32,
+    pub reason: DrawReason,
+}
+/// Action of the player, sent to the server or to the offline game
+#[derive(Deref, DerefMut)]
+pub struct GameActionEvent(pub GameAction);
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app.add_plugin(cards::CardsPlugin)
+            .add_plugin(feed::FeedPlugin)
+            .add_plugin(hand_layout::HandLayoutPlugin)
+            .add_plugin(seats::SeatsPlugin)
+            .add_plugin(selection::SelectionPlugin)
+            .add_plugin(snapshot::SnapshotPlugin)
+            .add_plugin(table::TablePlugin)
+            .add_plugin(themes::ThemesPlugin)
             .add_plugin(ui::GameUiPlugin)
             .add_event::<StartGameEvent>()
             .add_event::<ColorChosenEvent>()
@@ -59,8 +88,12 @@ impl Plugin for GamePlugin {
             .add_event::<GameEndEvent>()
             .add_event::<ExtraMessageEvent>()
             .add_event::<GameExitEvent>()
+            .add_event::<PlayerDrewEvent>()
+            .add_event::<GameActionEvent>()
+            .init_resource::<RoundResults>()
             .add_startup_system(load_assets)
             .add_system(start_game)
+            .add_system(send_game_actions)
             .add_system(game_exit)
             .add_system_set(
                 SystemSet::new()
@@ -69,6 +102,12 @@ impl Plugin for GamePlugin {
                     .with_system(execute_packets)
                     .with_system(to_be_removed),
             )
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(run_if_in_end_game_lobby)
+                    .with_system(return_to_lobby),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::EndLobby).with_system(reset_round))
             .add_system_set_to_stage(
                 CoreStage::PostUpdate,
                 SystemSet::new().with_system(game_end),
@@ -103,26 +142,22 @@ fn start_game(
     }
 }
 
-fn load_assets(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+/// The offline game reads the same events, the server only gets the actions of online games
+fn send_game_actions(
+    mut client: Client<Protocol, Channels>,
+    mut game_action_events: EventReader<GameActionEvent>,
+    offline_game: Option<Res<OfflineGame>>,
 ) {
-    let cards = asset_server.load("cards_02.png");
-    let cards_atlas = TextureAtlas::from_grid_with_padding(
-        cards,
-        Vec2::new(CARD_WIDTH, CARD_HEIGHT),
-        12,
-        6,
-        Vec2::splat(CARD_PADDING),
-    );
+    for GameActionEvent(action) in game_action_events.iter() {
+        if offline_game.is_none() {
+            client.send_message(Channels::Uno, action);
+        }
+    }
+}
 
+fn load_assets(mut commands: Commands, asset_server: Res<AssetServer>) {
     let background = asset_server.load("game_background.png");
 
-    commands.insert_resource(GameAssets {
-        cards: texture_atlases.add(cards_atlas),
-    });
-
     // Game background
     commands.spawn_bundle(SpriteBundle {
         texture: background,
@@ -146,24 +181,36 @@ fn execute_packets(
     uno_query: Query<Entity, With<CallUno>>,
     counter_uno_query: Query<Entity, With<CallCounterUno>>,
     mut draw_card_event: EventWriter<DrawCardEvent>,
+    mut player_drew_event: EventWriter<PlayerDrewEvent>,
     mut played_card_validation_event: EventWriter<PlayedCardValidationEvent>,
     mut card_played_event: EventWriter<CardPlayedEvent>,
     mut game_end_event: EventWriter<GameEndEvent>,
+    mut game_snapshot_event: EventWriter<GameSnapshotEvent>,
+    mut round_results: ResMut<RoundResults>,
 ) {
     for MessageEvent(_, message) in message_events.iter() {
         match message {
-            Protocol::GameEnd(_) => {
+            Protocol::GameEnd(game_end) => {
+                round_results.0 = (*game_end.results).clone();
                 game_end_event.send(GameEndEvent);
             }
             Protocol::DrawCard(card) => {
                 draw_card_event.send(DrawCardEvent((*card.color, *card.value).into()))
             }
+            Protocol::PlayerDrew(player_drew) => player_drew_event.send(PlayerDrewEvent {
+                player_id: *player_drew.player_id,
+                count: *player_drew.count,
+                reason: (*player_drew.reason).clone(),
+            }),
             Protocol::CardPlayed(card) => {
                 card_played_event.send(CardPlayedEvent((*card.color, *card.value).into()))
             }
             Protocol::CardValidation(validation) => {
                 played_card_validation_event.send(PlayedCardValidationEvent(*validation.valid));
             }
+            Protocol::GameSnapshot(snapshot) => {
+                game_snapshot_event.send(GameSnapshotEvent(snapshot.into()))
+            }
             Protocol::HaveToDrawCard(_) => {
                 commands.spawn().insert(DrawCard);
             }
@@ -195,7 +242,7 @@ fn to_be_removed(
         tbr.timer.tick(time.delta());
 
         if tbr.timer.finished() {
-            commands.entity(entity).despawn();
+            commands.entity(entity).despawn_recursive();
         }
     }
 }
@@ -204,7 +251,7 @@ fn game_end(
     mut commands: Commands,
     mut game_end_event: EventReader<GameEndEvent>,
     mut game_state: ResMut<State<GameState>>,
-    cards_query: Query<Entity, With<TextureAtlasSprite>>,
+    cards_query: Query<Entity, (With<TextureAtlasSprite>, Without<SeatCard>)>,
 ) {
     for GameEndEvent in game_end_event.iter() {
         if game_state.current() != &GameState::EndLobby {
@@ -212,11 +259,47 @@ fn game_end(
         }
 
         for entity in cards_query.iter() {
-            commands.entity(entity).despawn();
+            commands.entity(entity).despawn_recursive();
         }
     }
 }
 
+/// Someone asked to play again, the server brings the players back to the lobby of the game
+fn return_to_lobby(
+    mut message_events: EventReader<MessageEvent<Protocol, Channels>>,
+    mut game_state: ResMut<State<GameState>>,
+) {
+    for MessageEvent(_, message) in message_events.iter() {
+        if let Protocol::JoinLobby(_) = message {
+            if game_state.current() != &GameState::Lobbies {
+                game_state.set(GameState::Lobbies).unwrap();
+            }
+        }
+    }
+}
+
+/// Forget what is left of the last game, whether the players go back to a lobby or start a new
+/// round offline
+fn reset_round(
+    mut commands: Commands,
+    mut hand: ResMut<Hand>,
+    leftovers_query: Query<
+        Entity,
+        Or<(
+            With<DrawCard>,
+            With<CallUno>,
+            With<CallCounterUno>,
+            With<ChooseColor>,
+        )>,
+    >,
+) {
+    hand.size = 0;
+
+    for entity in leftovers_query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
 fn game_exit(
     mut commands: Commands,
     mut game_exit_event: EventReader<GameExitEvent>,
@@ -231,7 +314,7 @@ fn game_exit(
         hand.size = 0;
 
         for entity in cards_query.iter() {
-            commands.entity(entity).despawn();
+            commands.entity(entity).despawn_recursive();
         }
 
         for entity in players_query.iter() {
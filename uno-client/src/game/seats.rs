@@ -0,0 +1,351 @@
+use super::{
+    cards::{CardAnimation, CardPosition},
+    feed::draw_reason_name,
+    run_if_in_game,
+    snapshot::LastSnapshot,
+    themes::{CardBack, CardThemes},
+    PlayerDrewEvent, ToBeRemoved,
+};
+use crate::{
+    offline::{OfflineEntity, OfflineGame},
+    utils::constants::{
+        CARD_ANIMATION_TIME_S, CARD_HEIGHT, DECK_POS, DRAW_REASON_TIME_S, MAX_SEAT_CARDS,
+        SEAT_CARD_ANGLE, SEAT_CARD_SCALE, SEAT_CARD_SPACING, SEAT_Z,
+    },
+    GameState, PlayerId, Settings,
+};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use std::f32::consts::PI;
+use uno::{
+    card::Card,
+    network::protocol::{DrawReason, Player},
+    texts::Texts,
+};
+
+// Components
+/// Place of an opponent around the table, with their face-down hand
+#[derive(Component)]
+pub struct Seat {
+    pub player_id: u64,
+    /// Place of the seat in turn order, starting after the player
+    index: usize,
+    /// Number of cards drawn in the fan, none before the first update
+    cards: Option<usize>,
+    /// Why the player drew their last cards, shown for a moment after a penalty
+    draw_reason: Option<(DrawReason, Timer)>,
+}
+/// Face-down card of the hand of an opponent
+#[derive(Component)]
+pub struct SeatCard;
+
+// Ressources
+/// Position of the seat of the opponent who is playing, where the cards they play come from
+#[derive(Default)]
+pub struct ActiveSeat(pub Option<Vec2>);
+
+pub struct SeatsPlugin;
+
+impl Plugin for SeatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActiveSeat>()
+            .add_system_set(SystemSet::on_exit(GameState::Game).with_system(clear_seats))
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(run_if_in_game)
+                    .with_system(seat_players)
+                    .with_system(place_seats.after(seat_players))
+                    .with_system(update_seat_cards.after(place_seats))
+                    .with_system(seat_draws.after(place_seats))
+                    .with_system(seats_ui.after(place_seats)),
+            );
+    }
+}
+
+/// Opponents of the player in turn order, starting with the one playing after them
+fn opponents_in_turn_order(
+    players: &[&Player],
+    last_snapshot: &LastSnapshot,
+    player_id: Option<u64>,
+) -> Vec<u64> {
+    // The snapshot has the order of the server, the ids are only a guess
+    let mut order = match &last_snapshot.0 {
+        Some(snapshot) => snapshot.players.iter().map(|player| player.id).collect(),
+        None => {
+            let mut ids = players.iter().map(|player| *player.id).collect::<Vec<_>>();
+            ids.sort_unstable();
+            ids
+        }
+    };
+    order.retain(|id| players.iter().any(|player| *player.id == *id));
+
+    if let Some(position) = order.iter().position(|id| Some(*id) == player_id) {
+        order.rotate_left(position);
+        order.remove(0);
+    }
+
+    order
+}
+
+/// Seats are spread on an arc going from the left of the player to their right, so that the
+/// turns go clockwise
+fn seat_position(window: &Window, seat: usize, seats: usize) -> Vec2 {
+    let angle = PI - (seat + 1) as f32 * PI / (seats + 1) as f32;
+
+    Vec2::new(
+        angle.cos() * window.width() * 0.4,
+        angle.sin() * window.height() * 0.38 + DECK_POS.1 / 2.0,
+    )
+}
+
+/// Give a seat to every opponent, the seats are rebuilt when the players change
+fn seat_players(
+    mut commands: Commands,
+    players_query: Query<(&Player, Option<&OfflineEntity>)>,
+    seats_query: Query<(Entity, &Seat)>,
+    offline_game: Option<Res<OfflineGame>>,
+    last_snapshot: Res<LastSnapshot>,
+    player_id: Res<PlayerId>,
+) {
+    let players = players_query
+        .iter()
+        .filter(|(_, offline)| offline.is_some() == offline_game.is_some())
+        .map(|(player, _)| player)
+        .collect::<Vec<_>>();
+    let opponents = opponents_in_turn_order(&players, &last_snapshot, **player_id);
+
+    let mut seats = seats_query.iter().collect::<Vec<_>>();
+    seats.sort_by_key(|(_, seat)| seat.index);
+    if seats
+        .iter()
+        .map(|(_, seat)| seat.player_id)
+        .eq(opponents.iter().copied())
+    {
+        return;
+    }
+
+    for (entity, _) in seats {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    for (index, player_id) in opponents.into_iter().enumerate() {
+        commands
+            .spawn_bundle(TransformBundle::default())
+            .insert(Seat {
+                player_id,
+                index,
+                cards: None,
+                draw_reason: None,
+            });
+    }
+}
+
+fn place_seats(
+    windows: Res<Windows>,
+    mut seats_query: Query<(&Seat, &mut Transform)>,
+    players_query: Query<&Player>,
+    mut active_seat: ResMut<ActiveSeat>,
+) {
+    let window = windows.get_primary().unwrap();
+    let seats = seats_query.iter().count();
+
+    active_seat.0 = None;
+
+    for (seat, mut transform) in seats_query.iter_mut() {
+        let position = seat_position(window, seat.index, seats);
+        transform.translation = position.extend(SEAT_Z);
+
+        let playing = players_query
+            .iter()
+            .any(|player| *player.id == seat.player_id && *player.is_playing);
+        if playing {
+            active_seat.0 = Some(position);
+        }
+    }
+}
+
+/// Draw the face-down hand of each opponent
+fn update_seat_cards(
+    mut commands: Commands,
+    mut seats_query: Query<(Entity, &mut Seat)>,
+    players_query: Query<&Player>,
+    card_themes: Res<CardThemes>,
+) {
+    for (entity, mut seat) in seats_query.iter_mut() {
+        let player = match players_query
+            .iter()
+            .find(|player| *player.id == seat.player_id)
+        {
+            Some(player) => player,
+            None => continue,
+        };
+
+        let cards = (*player.hand_size).min(MAX_SEAT_CARDS);
+        if seat.cards == Some(cards) {
+            continue;
+        }
+
+        let (sprite, texture_atlas) = card_themes.sprite(&Card::back());
+        seat.cards = Some(cards);
+
+        commands.entity(entity).despawn_descendants();
+        commands.entity(entity).with_children(|parent| {
+            for index in 0..cards {
+                let offset = index as f32 - (cards - 1) as f32 / 2.0;
+                let mut card_transform =
+                    Transform::from_xyz(offset * SEAT_CARD_SPACING, 0.0, index as f32 * 0.001);
+                card_transform.scale = Vec3::new(SEAT_CARD_SCALE, SEAT_CARD_SCALE, 1.0);
+                card_transform.rotation = Quat::from_rotation_z(-offset * SEAT_CARD_ANGLE);
+
+                parent
+                    .spawn_bundle(SpriteSheetBundle {
+                        sprite: sprite.clone(),
+                        texture_atlas: texture_atlas.clone(),
+                        transform: card_transform,
+                        ..SpriteSheetBundle::default()
+                    })
+                    .insert(CardBack)
+                    .insert(SeatCard);
+            }
+        });
+    }
+}
+
+/// Send cards from the deck to the seat of the opponents who draw, and remember why they drew
+fn seat_draws(
+    mut commands: Commands,
+    mut player_drew_events: EventReader<PlayerDrewEvent>,
+    mut seats_query: Query<(&mut Seat, &Transform)>,
+    card_themes: Res<CardThemes>,
+    time: Res<Time>,
+) {
+    for (mut seat, _) in seats_query.iter_mut() {
+        if let Some((_, timer)) = &mut seat.draw_reason {
+            if timer.tick(time.delta()).finished() {
+                seat.draw_reason = None;
+            }
+        }
+    }
+
+    let (sprite, texture_atlas) = card_themes.sprite(&Card::back());
+
+    for PlayerDrewEvent {
+        player_id,
+        count,
+        reason,
+    } in player_drew_events.iter()
+    {
+        let (mut seat, transform) = match seats_query
+            .iter_mut()
+            .find(|(seat, _)| seat.player_id == *player_id)
+        {
+            Some(seat) => seat,
+            None => continue,
+        };
+
+        for _ in 0..(*count as usize).min(MAX_SEAT_CARDS) {
+            let mut card_transform = Transform::from_xyz(DECK_POS.0, DECK_POS.1, SEAT_Z + 0.1);
+            card_transform.scale = Vec3::new(SEAT_CARD_SCALE, SEAT_CARD_SCALE, 1.0);
+
+            commands
+                .spawn_bundle(SpriteSheetBundle {
+                    sprite: sprite.clone(),
+                    texture_atlas: texture_atlas.clone(),
+                    transform: card_transform,
+                    ..SpriteSheetBundle::default()
+                })
+                .insert(CardBack)
+                .insert(CardPosition(
+                    transform.translation.truncate().extend(SEAT_Z + 0.1),
+                ))
+                .insert(CardAnimation::default())
+                .insert(ToBeRemoved {
+                    timer: Timer::from_seconds(CARD_ANIMATION_TIME_S, false),
+                });
+        }
+
+        if !matches!(reason, DrawReason::Deal | DrawReason::Voluntary) {
+            seat.draw_reason = Some((
+                reason.clone(),
+                Timer::from_seconds(DRAW_REASON_TIME_S, false),
+            ));
+        }
+    }
+}
+
+/// Name and number of cards of each opponent under their seat
+fn seats_ui(
+    mut egui_context: ResMut<EguiContext>,
+    windows: Res<Windows>,
+    seats_query: Query<(&Seat, &Transform)>,
+    players_query: Query<&Player>,
+    settings: Res<Settings>,
+    texts: Res<Texts>,
+) {
+    let window = windows.get_primary().unwrap();
+    let to_screen = |position: Vec2| {
+        egui::Pos2::new(
+            position.x + window.width() / 2.0,
+            window.height() / 2.0 - position.y,
+        )
+    };
+
+    let ctx = egui_context.ctx_mut();
+    let visuals = ctx.style().visuals.clone();
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Background,
+        egui::Id::new("Seats"),
+    ));
+
+    for (seat, transform) in seats_query.iter() {
+        let player = match players_query
+            .iter()
+            .find(|player| *player.id == seat.player_id)
+        {
+            Some(player) => player,
+            None => continue,
+        };
+
+        let label_position = to_screen(
+            transform.translation.truncate() - Vec2::new(0.0, CARD_HEIGHT * SEAT_CARD_SCALE),
+        );
+        let color = if *player.is_playing {
+            egui::Color32::YELLOW
+        } else {
+            visuals.text_color()
+        };
+        let reason = seat
+            .draw_reason
+            .as_ref()
+            .and_then(|(reason, _)| draw_reason_name(reason, &texts, &settings.language));
+        let text = match reason {
+            Some(reason) => format!("{} ({}) {}", *player.username, *player.hand_size, reason),
+            None => format!("{} ({})", *player.username, *player.hand_size),
+        };
+        let galley = painter.layout_no_wrap(text, egui::FontId::proportional(16.0), color);
+
+        // Centered under the seat, on the background of the windows to be read on the table
+        let rect = egui::Rect::from_center_size(
+            label_position + egui::Vec2::new(0.0, galley.size().y / 2.0),
+            galley.size(),
+        );
+        painter.rect(
+            rect.expand(4.0),
+            4.0,
+            visuals.window_fill,
+            visuals.window_stroke,
+        );
+        painter.galley(rect.min, galley);
+    }
+}
+
+fn clear_seats(
+    mut commands: Commands,
+    seats_query: Query<Entity, With<Seat>>,
+    mut active_seat: ResMut<ActiveSeat>,
+) {
+    for entity in seats_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    active_seat.0 = None;
+}
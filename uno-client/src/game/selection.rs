@@ -0,0 +1,91 @@
+use super::{
+    cards::{
+        CardComponent, CardWaitingForValidation, Hand, HandItem, PlayCardEvent, ReorganizeHandEvent,
+    },
+    run_if_in_game,
+};
+use crate::{
+    controls::{ControlAction, ControlEvent},
+    Draggable, GameState,
+};
+use bevy::prelude::*;
+
+// Ressources
+/// Index of the card of the hand selected with the keyboard or a gamepad
+#[derive(Default)]
+pub struct HandSelection(pub Option<usize>);
+
+pub struct SelectionPlugin;
+
+impl Plugin for SelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HandSelection>()
+            .add_system_set(SystemSet::on_exit(GameState::Game).with_system(clear_selection))
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(run_if_in_game)
+                    .with_system(select_card),
+            );
+    }
+}
+
+fn clear_selection(mut selection: ResMut<HandSelection>) {
+    selection.0 = None;
+}
+
+fn select_card(
+    mut commands: Commands,
+    mut control_events: EventReader<ControlEvent>,
+    mut selection: ResMut<HandSelection>,
+    mut play_card_event: EventWriter<PlayCardEvent>,
+    mut reorganize_hand_event: EventWriter<ReorganizeHandEvent>,
+    hand: Res<Hand>,
+    hand_query: Query<(Entity, &HandItem, &CardComponent), With<Draggable>>,
+    waiting_query: Query<Entity, With<CardWaitingForValidation>>,
+) {
+    let previous_selection = selection.0;
+
+    // The hand may have shrunk since the card was selected
+    if let Some(index) = selection.0 {
+        if index >= hand.size {
+            selection.0 = hand.size.checked_sub(1);
+        }
+    }
+
+    for ControlEvent(action) in control_events.iter() {
+        if hand.size == 0 {
+            continue;
+        }
+
+        match action {
+            ControlAction::SelectPrevious => {
+                selection.0 = Some(match selection.0 {
+                    Some(index) if index > 0 => index - 1,
+                    _ => hand.size - 1,
+                });
+            }
+            ControlAction::SelectNext => {
+                selection.0 = Some(match selection.0 {
+                    Some(index) if index + 1 < hand.size => index + 1,
+                    _ => 0,
+                });
+            }
+            // Same as dropping the card on the discard
+            ControlAction::PlayCard if waiting_query.is_empty() => {
+                let selected_card = hand_query
+                    .iter()
+                    .find(|(_, item, _)| Some(item.index) == selection.0);
+
+                if let Some((entity, _, card)) = selected_card {
+                    commands.entity(entity).insert(CardWaitingForValidation);
+                    play_card_event.send(PlayCardEvent(card.0));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if selection.0 != previous_selection {
+        reorganize_hand_event.send(ReorganizeHandEvent);
+    }
+}
@@ -0,0 +1,188 @@
+use super::{
+    cards::{
+        CardComponent, CardPlayedEvent, CardWaitingForValidation, Discard, DrawCardEvent, Hand,
+        HandItem, ReorganizeHandEvent,
+    },
+    run_if_in_game, GameActionEvent, PlayedCardValidationEvent,
+};
+use crate::GameState;
+use bevy::prelude::*;
+use uno::{
+    card::{Card, Color},
+    network::protocol::{GameAction, GameSnapshot, SnapshotPlayer},
+};
+
+/// Client side copy of the last game snapshot received from the server
+#[derive(Clone)]
+pub struct Snapshot {
+    pub hand: Vec<Card>,
+    pub top_discard: Card,
+    pub current_color: Color,
+    /// Players of the game, in turn order
+    pub players: Vec<SnapshotPlayer>,
+    pub current_player: u64,
+    pub reverse_turn: bool,
+}
+
+impl From<&GameSnapshot> for Snapshot {
+    fn from(snapshot: &GameSnapshot) -> Self {
+        Snapshot {
+            hand: snapshot.hand(),
+            top_discard: snapshot.top_discard(),
+            current_color: snapshot.current_color(),
+            players: (*snapshot.players).clone(),
+            current_player: *snapshot.current_player,
+            reverse_turn: *snapshot.reverse_turn,
+        }
+    }
+}
+
+// Ressources
+#[derive(Default)]
+pub struct LastSnapshot(pub Option<Snapshot>);
+#[derive(Default)]
+struct PendingSnapshot(Option<Snapshot>);
+/// Number of frames since a card was last drawn or played
+#[derive(Default)]
+struct QuietFrames(u32);
+
+// Events
+pub struct GameSnapshotEvent(pub Snapshot);
+
+pub struct SnapshotPlugin;
+
+impl Plugin for SnapshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LastSnapshot>()
+            .init_resource::<PendingSnapshot>()
+            .init_resource::<QuietFrames>()
+            .add_event::<GameSnapshotEvent>()
+            .add_system_set(SystemSet::on_enter(GameState::Game).with_system(request_snapshot))
+            .add_system_set(SystemSet::on_exit(GameState::Game).with_system(clear_snapshot))
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(run_if_in_game)
+                    .with_system(receive_snapshot)
+                    .with_system(resync_on_invalid_card),
+            )
+            .add_system_set_to_stage(
+                CoreStage::PostUpdate,
+                SystemSet::new()
+                    .with_run_criteria(run_if_in_game)
+                    .with_system(track_table_activity),
+            )
+            .add_system_set_to_stage(
+                CoreStage::Last,
+                SystemSet::new()
+                    .with_run_criteria(run_if_in_game)
+                    .with_system(reconcile_snapshot),
+            );
+    }
+}
+
+fn request_snapshot(mut game_action_event: EventWriter<GameActionEvent>) {
+    game_action_event.send(GameActionEvent(GameAction::request_snapshot()));
+}
+
+fn clear_snapshot(mut last_snapshot: ResMut<LastSnapshot>, mut pending: ResMut<PendingSnapshot>) {
+    last_snapshot.0 = None;
+    pending.0 = None;
+}
+
+fn receive_snapshot(
+    mut snapshot_events: EventReader<GameSnapshotEvent>,
+    mut pending: ResMut<PendingSnapshot>,
+) {
+    // Only the most recent snapshot matters
+    if let Some(GameSnapshotEvent(snapshot)) = snapshot_events.iter().last() {
+        pending.0 = Some(snapshot.clone());
+    }
+}
+
+/// A refused card usually means the table is out of sync with the server
+fn resync_on_invalid_card(
+    mut game_action_event: EventWriter<GameActionEvent>,
+    mut validation_events: EventReader<PlayedCardValidationEvent>,
+) {
+    if validation_events.iter().any(|validation| !**validation) {
+        game_action_event.send(GameActionEvent(GameAction::request_snapshot()));
+    }
+}
+
+fn track_table_activity(
+    mut quiet_frames: ResMut<QuietFrames>,
+    mut draw_card_events: EventReader<DrawCardEvent>,
+    mut card_played_events: EventReader<CardPlayedEvent>,
+    waiting_query: Query<Entity, With<CardWaitingForValidation>>,
+) {
+    let activity = draw_card_events.iter().count() + card_played_events.iter().count();
+
+    if activity > 0 || waiting_query.iter().next().is_some() {
+        quiet_frames.0 = 0;
+    } else {
+        quiet_frames.0 += 1;
+    }
+}
+
+/// Rebuild the hand and the discard from the last snapshot. Cards drawn or played in the
+/// last frames may not have been spawned yet, so the snapshot is only applied once the table
+/// has been quiet for two frames
+fn reconcile_snapshot(
+    mut commands: Commands,
+    mut pending: ResMut<PendingSnapshot>,
+    mut last_snapshot: ResMut<LastSnapshot>,
+    mut hand: ResMut<Hand>,
+    quiet_frames: Res<QuietFrames>,
+    mut draw_card_event: EventWriter<DrawCardEvent>,
+    mut card_played_event: EventWriter<CardPlayedEvent>,
+    mut reorganize_hand_event: EventWriter<ReorganizeHandEvent>,
+    mut hand_query: Query<(Entity, &CardComponent, &mut HandItem)>,
+    discard_query: Query<(Option<&CardComponent>, &Transform), With<Discard>>,
+) {
+    if quiet_frames.0 < 2 {
+        return;
+    }
+
+    let snapshot = match pending.0.take() {
+        Some(snapshot) => snapshot,
+        None => return,
+    };
+
+    // Match every card of the hand with a card of the snapshot, the ones left on either side
+    // are respectively ghost cards and missing cards
+    let mut missing_cards = snapshot.hand.clone();
+    let mut kept_items = vec![];
+
+    for (entity, card, item) in hand_query.iter() {
+        if let Some(idx) = missing_cards.iter().position(|&c| c == card.0) {
+            missing_cards.remove(idx);
+            kept_items.push((item.index, entity));
+        } else {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    kept_items.sort_unstable();
+    for (new_index, (_, entity)) in kept_items.iter().enumerate() {
+        if let Ok((_, _, mut item)) = hand_query.get_mut(*entity) {
+            item.index = new_index;
+        }
+    }
+    hand.size = kept_items.len();
+
+    for card in missing_cards {
+        draw_card_event.send(DrawCardEvent(card));
+    }
+
+    let top_discard = discard_query
+        .iter()
+        .max_by(|(_, t1), (_, t2)| t1.translation.z.total_cmp(&t2.translation.z))
+        .and_then(|(card, _)| card.map(|card| card.0));
+
+    if top_discard != Some(snapshot.top_discard) {
+        card_played_event.send(CardPlayedEvent(snapshot.top_discard));
+    }
+
+    reorganize_hand_event.send(ReorganizeHandEvent);
+    last_snapshot.0 = Some(snapshot);
+}
@@ -0,0 +1,129 @@
+use super::{
+    run_if_in_game,
+    themes::{CardBack, CardThemes},
+};
+use crate::{
+    offline::{OfflineEntity, OfflineGame},
+    utils::constants::{CARD_HEIGHT, CARD_SCALE, DECK_LAYER_OFFSET, DECK_POS, MAX_DECK_LAYERS},
+    GameState, PlayerId, Settings,
+};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use uno::{
+    card::Card,
+    network::protocol::{Player, TableState},
+    texts::{TextId, Texts},
+    Deck,
+};
+
+// Components
+/// Back of a card drawn under the deck to show how many cards are left in it
+#[derive(Component)]
+pub struct DeckLayer(usize);
+
+pub struct TablePlugin;
+
+impl Plugin for TablePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(GameState::Game).with_system(setup_deck_layers))
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(run_if_in_game)
+                    .with_system(deck_thickness)
+                    .with_system(table_state_window),
+            );
+    }
+}
+
+fn setup_deck_layers(mut commands: Commands, card_themes: Res<CardThemes>) {
+    for layer in 0..MAX_DECK_LAYERS {
+        let offset = (layer + 1) as f32 * DECK_LAYER_OFFSET;
+        let mut transform = Transform::from_xyz(
+            DECK_POS.0 - offset,
+            DECK_POS.1 - offset,
+            0.1 - (layer + 1) as f32 * 0.001,
+        );
+        transform.scale = Vec3::new(CARD_SCALE, CARD_SCALE, 1.0);
+        let (sprite, texture_atlas) = card_themes.sprite(&Card::back());
+
+        commands
+            .spawn_bundle(SpriteSheetBundle {
+                sprite,
+                texture_atlas,
+                transform,
+                ..SpriteSheetBundle::default()
+            })
+            .insert(DeckLayer(layer))
+            .insert(CardBack);
+    }
+}
+
+fn deck_thickness(
+    table_state_query: Query<&TableState, Changed<TableState>>,
+    mut layers_query: Query<(&DeckLayer, &mut Visibility)>,
+) {
+    if let Ok(table_state) = table_state_query.get_single() {
+        let full_deck_size = Deck::full().size();
+        let visible_layers =
+            (*table_state.draw_pile_size * MAX_DECK_LAYERS + full_deck_size - 1) / full_deck_size;
+
+        for (DeckLayer(layer), mut visibility) in layers_query.iter_mut() {
+            visibility.is_visible = *layer < visible_layers;
+        }
+    }
+}
+
+fn table_state_window(
+    mut egui_context: ResMut<EguiContext>,
+    table_state_query: Query<&TableState>,
+    players_query: Query<(&Player, Option<&OfflineEntity>)>,
+    offline_game: Option<Res<OfflineGame>>,
+    player_id: Res<PlayerId>,
+    settings: Res<Settings>,
+    texts: Res<Texts>,
+) {
+    let table_state = match table_state_query.get_single() {
+        Ok(table_state) => table_state,
+        Err(_) => return,
+    };
+    let language = &settings.language;
+
+    let current_player = players_query
+        .iter()
+        .filter(|(_, offline)| offline.is_some() == offline_game.is_some())
+        .map(|(player, _)| player)
+        .find(|player| *player.id == *table_state.current_player);
+    let turn = match current_player {
+        Some(player) if **player_id == Some(*player.id) => texts.get(TextId::YourTurn, language),
+        Some(player) => texts.format(
+            TextId::PlayerTurn,
+            language,
+            &[("player", player.username.as_str())],
+        ),
+        None => String::new(),
+    };
+
+    let arrow = if *table_state.reverse_turn {
+        "⬅"
+    } else {
+        "➡"
+    };
+
+    egui::Area::new("Table state")
+        .anchor(
+            egui::Align2::CENTER_CENTER,
+            [0.0, -(DECK_POS.1 + CARD_HEIGHT * CARD_SCALE / 2.0 + 60.0)],
+        )
+        .interactable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.vertical_centered(|ui| {
+                ui.label(egui::RichText::new(arrow).size(40.0).strong());
+                ui.label(egui::RichText::new(turn).strong());
+                ui.label(format!(
+                    "{}: {}",
+                    texts.get(TextId::DrawPile, language),
+                    *table_state.draw_pile_size
+                ));
+            });
+        });
+}
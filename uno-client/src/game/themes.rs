@@ -0,0 +1,336 @@
+use super::CardComponent;
+use crate::{
+    utils::constants::{CARD_HEIGHT, CARD_WIDTH},
+    Settings,
+};
+use bevy::{prelude::*, sprite::Rect};
+use serde::Deserialize;
+use uno::card::{Card, Color, Value};
+
+/// Theme used when the one of the settings doesn't exist, and for the cards missing from a theme
+pub const DEFAULT_THEME: &str = "classic";
+
+/// Manifests embedded in the binary, with the id of their theme
+const BUILTIN_THEMES: [(&str, &str); 3] = [
+    ("classic", include_str!("../../assets/themes/classic.ron")),
+    ("flat", include_str!("../../assets/themes/flat.ron")),
+    ("retro", include_str!("../../assets/themes/retro.ron")),
+];
+
+pub struct ThemesPlugin;
+
+/// Content of a theme manifest: the atlas of the cards and where each card is in it
+#[derive(Deserialize, Debug, Clone)]
+pub struct CardTheme {
+    pub name: String,
+    /// Path of the atlas, relative to the assets folder
+    pub texture: String,
+    pub tile_size: (f32, f32),
+    pub columns: usize,
+    pub rows: usize,
+    #[serde(default)]
+    pub padding: (f32, f32),
+    /// Themes without a back use the one of the default theme
+    #[serde(default)]
+    pub back: Option<usize>,
+    pub wild: usize,
+    pub wild_four: usize,
+    /// Index of the cards of each color, from 1 to 9, then 0, draw two, skip and reverse
+    pub yellow: Vec<usize>,
+    pub red: Vec<usize>,
+    pub blue: Vec<usize>,
+    pub green: Vec<usize>,
+}
+
+impl CardTheme {
+    /// Number of cards of each color in a manifest
+    const COLORED_CARDS: usize = 13;
+
+    pub fn parse(content: &str) -> Result<CardTheme, String> {
+        let theme: CardTheme = ron::de::from_str(content).map_err(|err| err.to_string())?;
+        let tiles = theme.columns * theme.rows;
+
+        for (color, indices) in [
+            ("yellow", &theme.yellow),
+            ("red", &theme.red),
+            ("blue", &theme.blue),
+            ("green", &theme.green),
+        ] {
+            if indices.len() != Self::COLORED_CARDS {
+                return Err(format!(
+                    "{color} has {} cards instead of {}",
+                    indices.len(),
+                    Self::COLORED_CARDS
+                ));
+            }
+        }
+
+        let all_indices = [theme.wild, theme.wild_four]
+            .into_iter()
+            .chain(theme.back)
+            .chain(theme.yellow.iter().copied())
+            .chain(theme.red.iter().copied())
+            .chain(theme.blue.iter().copied())
+            .chain(theme.green.iter().copied());
+
+        for index in all_indices {
+            if index >= tiles {
+                return Err(format!(
+                    "index {index} is outside of the {}x{} grid",
+                    theme.columns, theme.rows
+                ));
+            }
+        }
+
+        Ok(theme)
+    }
+
+    /// Index of a card in the atlas, if the theme has it
+    pub fn index(&self, card: &Card) -> Option<usize> {
+        match card.value {
+            Value::Back => self.back,
+            Value::Wild => Some(self.wild),
+            Value::WildFour => Some(self.wild_four),
+            value => {
+                let indices = match card.color {
+                    Color::Yellow => &self.yellow,
+                    Color::Red => &self.red,
+                    Color::Blue => &self.blue,
+                    Color::Green => &self.green,
+                    Color::Black => return None,
+                };
+
+                indices.get(value as usize - 1).copied()
+            }
+        }
+    }
+
+    fn atlas(&self, asset_server: &AssetServer) -> TextureAtlas {
+        TextureAtlas::from_grid_with_padding(
+            asset_server.load(self.texture.as_str()),
+            Vec2::new(self.tile_size.0, self.tile_size.1),
+            self.columns,
+            self.rows,
+            Vec2::new(self.padding.0, self.padding.1),
+        )
+    }
+}
+
+struct LoadedTheme {
+    id: String,
+    theme: CardTheme,
+    atlas: Handle<TextureAtlas>,
+}
+
+// Ressources
+/// Known card themes, the builtin ones first
+pub struct CardThemes {
+    themes: Vec<LoadedTheme>,
+    current: usize,
+}
+
+impl CardThemes {
+    /// Add a theme, replacing the one with the same id
+    pub fn add(
+        &mut self,
+        id: &str,
+        theme: CardTheme,
+        asset_server: &AssetServer,
+        texture_atlases: &mut Assets<TextureAtlas>,
+    ) {
+        let atlas = texture_atlases.add(theme.atlas(asset_server));
+        let loaded_theme = LoadedTheme {
+            id: id.to_owned(),
+            theme,
+            atlas,
+        };
+
+        match self.themes.iter_mut().find(|loaded| loaded.id == id) {
+            Some(loaded) => *loaded = loaded_theme,
+            None => self.themes.push(loaded_theme),
+        }
+    }
+
+    /// Ids of the known themes with their name
+    pub fn themes(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.themes
+            .iter()
+            .map(|loaded| (loaded.id.as_str(), loaded.theme.name.as_str()))
+    }
+
+    /// Name of a theme, its id if it isn't known
+    pub fn theme_name<'a>(&'a self, id: &'a str) -> &'a str {
+        self.themes
+            .iter()
+            .find(|loaded| loaded.id == id)
+            .map_or(id, |loaded| loaded.theme.name.as_str())
+    }
+
+    /// Sprite of a card with the current theme, or with the default one if the current theme
+    /// doesn't have it. Cards no theme has, like a black number, get the default back
+    pub fn sprite(&self, card: &Card) -> (TextureAtlasSprite, Handle<TextureAtlas>) {
+        let default = &self.themes[0];
+        let (index, atlas) = [&self.themes[self.current], default]
+            .into_iter()
+            .find_map(|loaded| Some((loaded.theme.index(card)?, &loaded.atlas)))
+            .unwrap_or((default.theme.back.unwrap_or_default(), &default.atlas));
+
+        let sprite = TextureAtlasSprite {
+            index,
+            // Every theme is drawn at the size of the default one, so the layout doesn't change
+            custom_size: Some(Vec2::new(CARD_WIDTH, CARD_HEIGHT)),
+            ..TextureAtlasSprite::default()
+        };
+
+        (sprite, atlas.clone_weak())
+    }
+
+    /// Texture of the atlas of a card and the part of it showing the card, with coordinates
+    /// from 0 to 1, to draw the card in the interface. `None` until the atlas is loaded
+    pub fn texture(
+        &self,
+        card: &Card,
+        texture_atlases: &Assets<TextureAtlas>,
+    ) -> Option<(Handle<Image>, Rect)> {
+        let (sprite, atlas) = self.sprite(card);
+        let atlas = texture_atlases.get(&atlas)?;
+        let rect = atlas.textures.get(sprite.index)?;
+
+        Some((
+            atlas.texture.clone(),
+            Rect {
+                min: rect.min / atlas.size,
+                max: rect.max / atlas.size,
+            },
+        ))
+    }
+}
+
+// Components
+/// Back of a card that isn't a `CardComponent`, like the deck
+#[derive(Component)]
+pub struct CardBack;
+
+impl Plugin for ThemesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(load_themes)
+            .add_system(select_theme)
+            .add_system(apply_theme.after(select_theme));
+    }
+}
+
+fn load_themes(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    settings: Res<Settings>,
+) {
+    let mut card_themes = CardThemes {
+        themes: vec![],
+        current: 0,
+    };
+
+    for (id, content) in BUILTIN_THEMES {
+        let theme = CardTheme::parse(content).expect("Invalid builtin theme");
+        card_themes.add(id, theme, &asset_server, &mut texture_atlases);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    load_theme_packs(&mut card_themes, &asset_server, &mut texture_atlases);
+
+    if let Some(current) = card_themes
+        .themes
+        .iter()
+        .position(|loaded| loaded.id == settings.card_theme)
+    {
+        card_themes.current = current;
+    }
+
+    commands.insert_resource(card_themes);
+}
+
+/// Add the themes of `assets/themes`, a new theme only needs a `<id>.ron` manifest there and
+/// the atlas it points to. The builtin themes can't be replaced, the cards missing from the
+/// other themes are taken from the default one
+#[cfg(not(target_arch = "wasm32"))]
+fn load_theme_packs(
+    card_themes: &mut CardThemes,
+    asset_server: &AssetServer,
+    texture_atlases: &mut Assets<TextureAtlas>,
+) {
+    let themes_dir = bevy::asset::FileAssetIo::get_root_path()
+        .join("assets")
+        .join("themes");
+
+    let entries = match std::fs::read_dir(themes_dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+        let id = match (path.extension(), path.file_stem()) {
+            (Some(extension), Some(stem)) if extension == "ron" => stem.to_string_lossy(),
+            _ => continue,
+        };
+
+        // The manifests of the builtin themes are there too
+        if BUILTIN_THEMES
+            .iter()
+            .any(|(builtin_id, _)| *builtin_id == id)
+        {
+            continue;
+        }
+
+        let result = std::fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|content| CardTheme::parse(&content));
+
+        match result {
+            Ok(theme) => card_themes.add(&id, theme, asset_server, texture_atlases),
+            Err(err) => warn!("Couldn't load the card theme {}: {err}", path.display()),
+        }
+    }
+}
+
+fn select_theme(settings: Res<Settings>, mut card_themes: ResMut<CardThemes>) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    let current = card_themes
+        .themes
+        .iter()
+        .position(|loaded| loaded.id == settings.card_theme)
+        .unwrap_or(0);
+
+    // Only touched when the theme changes, so that the cards aren't redrawn every time
+    if card_themes.current != current {
+        card_themes.current = current;
+    }
+}
+
+/// Redraw the cards already on the table with the new theme
+fn apply_theme(
+    card_themes: Res<CardThemes>,
+    mut cards_query: Query<
+        (
+            &mut TextureAtlasSprite,
+            &mut Handle<TextureAtlas>,
+            Option<&CardComponent>,
+        ),
+        Or<(With<CardComponent>, With<CardBack>)>,
+    >,
+) {
+    if !card_themes.is_changed() {
+        return;
+    }
+
+    for (mut sprite, mut atlas, card) in cards_query.iter_mut() {
+        let card = card.map_or_else(Card::back, |card| card.0);
+        let (new_sprite, new_atlas) = card_themes.sprite(&card);
+
+        sprite.index = new_sprite.index;
+        sprite.custom_size = new_sprite.custom_size;
+        *atlas = new_atlas;
+    }
+}
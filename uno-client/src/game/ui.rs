@@ -1,22 +1,26 @@
 use super::{
-    run_if_in_end_game_lobby, run_if_in_game, CallCounterUno, CallUno, ChooseColor,
-    ColorChosenEvent, DrawCard,
+    cards::{HandSort, ReorganizeHandEvent},
+    hand_layout::HandScroll,
+    run_if_in_end_game_lobby, run_if_in_game,
+    snapshot::LastSnapshot,
+    themes::CardThemes,
+    CallCounterUno, CallUno, ChooseColor, ColorChosenEvent, DrawCard, GameActionEvent,
+    RoundResults,
 };
 use crate::{
+    accessibility::{paint_color_symbol, PLAYABLE_COLORS},
+    controls::{ControlAction, ControlEvent},
     game::GameExitEvent,
-    utils::constants::{CARD_SCALE, CARD_WIDTH, COLORS},
+    offline::{OfflineEntity, OfflineGame},
+    utils::constants::{CARD_HEIGHT, CARD_SCALE, CARD_WIDTH, END_CARD_SCALE},
     PlayerId, Settings,
 };
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContext};
 use itertools::Itertools;
-use naia_bevy_client::Client;
 use uno::{
-    card::{Card, Color},
-    network::{
-        protocol::{self, CurrentColor, GameExit, Player, Protocol},
-        Channels,
-    },
+    card::Color,
+    network::protocol::{CurrentColor, GameAction, Player},
     texts::{TextId, Texts},
 };
 
@@ -35,7 +39,9 @@ impl Plugin for GameUiPlugin {
                 .with_run_criteria(run_if_in_game)
                 .with_system(choose_color_window)
                 .with_system(call_uno_window)
-                .with_system(draw_card_window),
+                .with_system(draw_card_window)
+                .with_system(hand_sort_window)
+                .with_system(hand_scroll_window),
         )
         .add_system_set_to_stage(
             CoreStage::PostUpdate,
@@ -47,15 +53,49 @@ impl Plugin for GameUiPlugin {
 }
 
 fn end_game_lobby(
-    mut client: Client<Protocol, Channels>,
     mut egui_context: ResMut<EguiContext>,
     mut game_exit_event: EventWriter<GameExitEvent>,
-    players_query: Query<(Entity, &Player)>,
+    mut game_action_event: EventWriter<GameActionEvent>,
+    players_query: Query<(&Player, Option<&OfflineEntity>)>,
+    offline_game: Option<Res<OfflineGame>>,
+    round_results: Res<RoundResults>,
+    card_themes: Res<CardThemes>,
+    texture_atlases: Res<Assets<TextureAtlas>>,
     player_id: Res<PlayerId>,
     settings: Res<Settings>,
     texts: Res<Texts>,
+    mut asked_to_play_again: Local<bool>,
 ) {
-    let language = settings.language;
+    let language = &settings.language;
+    if round_results.is_changed() {
+        *asked_to_play_again = false;
+    }
+
+    let username = |id: u64| {
+        players_query
+            .iter()
+            .filter(|(_, offline)| offline.is_some() == offline_game.is_some())
+            .find(|(player, _)| *player.id == id)
+            .map(|(player, _)| (*player.username).clone())
+            .unwrap_or_default()
+    };
+
+    // The remaining cards are drawn with the textures of the theme, which egui must know first
+    let results = round_results
+        .0
+        .iter()
+        .sorted_by_key(|result| result.total_score)
+        .map(|result| {
+            let cards = result
+                .hand()
+                .iter()
+                .filter_map(|card| card_themes.texture(card, &texture_atlases))
+                .map(|(texture, rect)| (egui_context.add_image(texture), rect))
+                .collect::<Vec<_>>();
+
+            (result, username(result.id), cards)
+        })
+        .collect::<Vec<_>>();
 
     egui::Window::new(egui::RichText::new(texts.get(TextId::EndGameTitle, language)).strong())
         .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
@@ -64,51 +104,77 @@ fn end_game_lobby(
         .collapsible(false)
         .resizable(false)
         .show(egui_context.ctx_mut(), |ui| {
-            ui.columns(3, |cols| {
-                for (i, col) in cols.iter_mut().enumerate() {
-                    col.label(
-                        egui::RichText::new(match i {
-                            0 => texts.get(TextId::Username, language),
-                            1 => texts.get(TextId::Score, language),
-                            2 => texts.get(TextId::RemainingCards, language),
-                            _ => unreachable!(),
-                        })
-                        .heading()
-                        .strong(),
-                    );
-                }
-            });
-
-            ui.separator();
-
-            let players = players_query
-                .iter()
-                .sorted_by(|(_, p1), (_, p2)| p1.score.cmp(&p2.score));
-
-            for (_, player) in players {
-                ui.columns(3, |cols| {
-                    if *player.id == player_id.unwrap_or(0) {
-                        cols[0].label(egui::RichText::new(&*player.username).strong());
-                        cols[1].label(egui::RichText::new(player.score.to_string()).strong());
-                    } else {
-                        cols[0].label(&*player.username);
-                        cols[1].label(player.score.to_string());
+            egui::Grid::new("Results")
+                .striped(true)
+                .spacing([20.0, 8.0])
+                .show(ui, |ui| {
+                    for text_id in [
+                        TextId::Username,
+                        TextId::RoundPoints,
+                        TextId::Score,
+                        TextId::RemainingCards,
+                        TextId::Turns,
+                        TextId::CardsPlayed,
+                        TextId::Penalties,
+                    ] {
+                        ui.label(egui::RichText::new(texts.get(text_id, language)).strong());
+                    }
+                    ui.end_row();
+
+                    for (result, username, cards) in &results {
+                        let me = Some(result.id) == **player_id;
+                        let text = |text: String| {
+                            let text = egui::RichText::new(text);
+                            if me {
+                                text.strong()
+                            } else {
+                                text
+                            }
+                        };
+
+                        ui.label(text(username.clone()));
+                        ui.label(text(format!("+{}", result.round_score)));
+                        ui.label(text(result.total_score.to_string()));
+                        ui.horizontal_wrapped(|ui| {
+                            ui.set_max_width(250.0);
+                            ui.spacing_mut().item_spacing.x = 2.0;
+
+                            for (texture, rect) in cards {
+                                ui.add(
+                                    egui::Image::new(
+                                        *texture,
+                                        [CARD_WIDTH * END_CARD_SCALE, CARD_HEIGHT * END_CARD_SCALE],
+                                    )
+                                    .uv(
+                                        egui::Rect::from_min_max(
+                                            egui::pos2(rect.min.x, rect.min.y),
+                                            egui::pos2(rect.max.x, rect.max.y),
+                                        ),
+                                    ),
+                                );
+                            }
+                        });
+                        ui.label(result.turns.to_string());
+                        ui.label(result.cards_played.to_string());
+                        ui.label(result.penalties.to_string());
+                        ui.end_row();
                     }
-
-                    small_card_count(&mut cols[2], player, Color::Yellow);
                 });
 
-                ui.separator();
-            }
+            ui.separator();
 
             ui.vertical_centered(|ui| {
-                // if ui.button("Replay").clicked() {
-                //     todo!();
-                // }
+                // Online, the game starts again once every player has asked for it
+                if *asked_to_play_again {
+                    ui.label(texts.get(TextId::WaitingForPlayers, language));
+                } else if ui.button(texts.get(TextId::PlayAgain, language)).clicked() {
+                    game_action_event.send(GameActionEvent(GameAction::play_again()));
+                    *asked_to_play_again = offline_game.is_none();
+                }
 
                 if ui.button(texts.get(TextId::BackToMenu, language)).clicked() {
                     game_exit_event.send(GameExitEvent);
-                    client.send_message(Channels::Uno, &GameExit::new());
+                    game_action_event.send(GameActionEvent(GameAction::exit_game()));
                 }
             })
         });
@@ -116,21 +182,29 @@ fn end_game_lobby(
 
 fn players_panel(
     mut egui_context: ResMut<EguiContext>,
-    players_query: Query<&Player>,
+    players_query: Query<(&Player, Option<&OfflineEntity>)>,
     current_color_query: Query<&CurrentColor>,
+    offline_game: Option<Res<OfflineGame>>,
+    last_snapshot: Res<LastSnapshot>,
     player_id: Res<PlayerId>,
+    settings: Res<Settings>,
 ) {
     egui::TopBottomPanel::top("Players").show(egui_context.ctx_mut(), |ui| {
         ui.vertical_centered(|ui| {
-            let size = players_query.iter().count();
-            let current_color = match current_color_query.get_single() {
-                Ok(CurrentColor { color }) => (**color).into(),
-                Err(_) => Color::Black,
+            // Offline, the players seen in the lobbies of the server aren't part of the game
+            let players = players_query
+                .iter()
+                .filter(|(_, offline)| offline.is_some() == offline_game.is_some())
+                .map(|(player, _)| player)
+                .sorted_by(|p1, p2| p1.id.cmp(&p2.id))
+                .collect::<Vec<_>>();
+            let current_color = match (current_color_query.get_single(), &last_snapshot.0) {
+                (Ok(CurrentColor { color }), _) => (**color).into(),
+                (Err(_), Some(snapshot)) => snapshot.current_color,
+                (Err(_), None) => Color::Black,
             };
 
-            let players = players_query.iter().sorted_by(|p1, p2| p1.id.cmp(&p2.id));
-
-            ui.columns(size, |cols| {
+            ui.columns(players.len(), |cols| {
                 for (col, player) in cols.iter_mut().zip(players) {
                     col.vertical_centered(|ui| {
                         let mut text = egui::RichText::new(&*player.username);
@@ -140,7 +214,7 @@ fn players_panel(
                         }
 
                         ui.label(text);
-                        small_card_count(ui, player, current_color);
+                        small_card_count(ui, player, current_color, &settings);
                     });
                 }
             });
@@ -153,62 +227,87 @@ fn choose_color_window(
     mut egui_context: ResMut<EguiContext>,
     choose_color: Query<Entity, With<ChooseColor>>,
     mut color_chosen_event: EventWriter<ColorChosenEvent>,
+    mut control_events: EventReader<ControlEvent>,
+    settings: Res<Settings>,
+    texts: Res<Texts>,
 ) {
+    let mut chosen_color = control_events.iter().find_map(|action| action.color());
+
     if let Ok(entity) = choose_color.get_single() {
-        egui::Window::new(egui::RichText::new("Choose color").strong())
-            .anchor(
-                egui::Align2::CENTER_CENTER,
-                [0.0, CARD_WIDTH * CARD_SCALE / 2.0 + 30.0],
-            )
-            .collapsible(false)
-            .resizable(false)
-            .show(egui_context.ctx_mut(), |ui| {
-                ui.horizontal(|ui| {
-                    const CARD_WIDTH: f32 = 30.0;
-                    const CARD_HEIGHT: f32 = 46.2;
-
-                    for (card_color, egui_color) in COLORS {
-                        let size = egui::Vec2::new(CARD_WIDTH, CARD_HEIGHT);
-                        let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click());
-
-                        ui.painter().rect_filled(rect, 3.0, egui_color);
-
-                        if response.clicked() {
-                            commands.entity(entity).despawn();
-                            color_chosen_event.send(ColorChosenEvent(card_color));
-                        }
+        egui::Window::new(
+            egui::RichText::new(texts.get(TextId::ChooseColor, &settings.language)).strong(),
+        )
+        .anchor(
+            egui::Align2::CENTER_CENTER,
+            [0.0, CARD_WIDTH * CARD_SCALE / 2.0 + 30.0],
+        )
+        .collapsible(false)
+        .resizable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                const CARD_WIDTH: f32 = 30.0;
+                const CARD_HEIGHT: f32 = 46.2;
+
+                for card_color in PLAYABLE_COLORS {
+                    let size = egui::Vec2::new(CARD_WIDTH, CARD_HEIGHT);
+                    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click());
+
+                    ui.painter()
+                        .rect_filled(rect, 3.0, settings.palette.color32(card_color));
+                    if settings.colorblind_mode {
+                        paint_color_symbol(ui.painter(), rect, card_color);
                     }
-                });
+
+                    if response.clicked() {
+                        chosen_color = Some(card_color);
+                    }
+                }
             });
+        });
+
+        if let Some(color) = chosen_color {
+            commands.entity(entity).despawn();
+            color_chosen_event.send(ColorChosenEvent(color));
+        }
     }
 }
 
 fn call_uno_window(
     mut commands: Commands,
     mut egui_context: ResMut<EguiContext>,
-    mut client: Client<Protocol, Channels>,
+    mut game_action_event: EventWriter<GameActionEvent>,
     call_uno: Query<Entity, With<CallUno>>,
     call_counter_uno: Query<Entity, With<CallCounterUno>>,
+    mut control_events: EventReader<ControlEvent>,
+    settings: Res<Settings>,
+    texts: Res<Texts>,
 ) {
+    let actions = control_events
+        .iter()
+        .map(|action| **action)
+        .collect::<Vec<_>>();
+
     if let Ok(entity) = call_uno.get_single() {
         button_window(
             egui_context.ctx_mut(),
-            "Uno!",
+            &texts.get(TextId::Uno, &settings.language),
             egui::Align2::RIGHT_BOTTOM,
             egui::Vec2::new(-50.0, -50.0),
+            actions.contains(&ControlAction::Uno),
             || {
-                client.send_message(Channels::Uno, &protocol::Uno::new());
+                game_action_event.send(GameActionEvent(GameAction::uno()));
                 commands.entity(entity).despawn();
             },
         );
     } else if let Ok(entity) = call_counter_uno.get_single() {
         button_window(
             egui_context.ctx_mut(),
-            "Counter Uno!",
+            &texts.get(TextId::CounterUno, &settings.language),
             egui::Align2::RIGHT_BOTTOM,
             egui::Vec2::new(-50.0, -50.0),
+            actions.contains(&ControlAction::CounterUno),
             || {
-                client.send_message(Channels::Uno, &protocol::CounterUno::new());
+                game_action_event.send(GameActionEvent(GameAction::counter_uno()));
                 commands.entity(entity).despawn();
             },
         );
@@ -219,27 +318,100 @@ fn draw_card_window(
     mut commands: Commands,
     mut egui_context: ResMut<EguiContext>,
     draw_card_query: Query<Entity, With<DrawCard>>,
-    mut client: Client<Protocol, Channels>,
+    mut game_action_event: EventWriter<GameActionEvent>,
+    mut control_events: EventReader<ControlEvent>,
+    settings: Res<Settings>,
+    texts: Res<Texts>,
 ) {
+    let draw_pressed = control_events
+        .iter()
+        .any(|action| **action == ControlAction::DrawCard);
+
     if let Ok(entity) = draw_card_query.get_single() {
         button_window(
             egui_context.ctx_mut(),
-            "Draw card",
+            &texts.get(TextId::DrawCard, &settings.language),
             egui::Align2::LEFT_BOTTOM,
             egui::Vec2::new(50.0, -50.0),
+            draw_pressed,
             || {
-                client.send_message(Channels::Uno, &protocol::DrawCard::new(Card::back()));
+                game_action_event.send(GameActionEvent(GameAction::draw_card()));
                 commands.entity(entity).despawn();
             },
         );
     }
 }
 
+fn hand_sort_window(
+    mut egui_context: ResMut<EguiContext>,
+    mut reorganize_hand_event: EventWriter<ReorganizeHandEvent>,
+    mut settings: ResMut<Settings>,
+    texts: Res<Texts>,
+) {
+    let language = &settings.language.clone();
+
+    egui::Window::new(texts.get(TextId::SortHand, language))
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::Vec2::new(-50.0, -150.0))
+        .collapsible(false)
+        .resizable(false)
+        .title_bar(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label(texts.get(TextId::SortHand, language));
+
+                for hand_sort in HandSort::ALL {
+                    if ui
+                        .selectable_value(
+                            &mut settings.hand_sort,
+                            hand_sort,
+                            texts.get(hand_sort.text_id(), language),
+                        )
+                        .changed()
+                    {
+                        reorganize_hand_event.send(ReorganizeHandEvent);
+                    }
+                }
+            });
+        });
+}
+
+/// Arrows to scroll a hand too large to be shown at once, for the screens without a mouse wheel
+fn hand_scroll_window(
+    mut egui_context: ResMut<EguiContext>,
+    mut reorganize_hand_event: EventWriter<ReorganizeHandEvent>,
+    mut scroll: ResMut<HandScroll>,
+) {
+    if !scroll.scrollable {
+        return;
+    }
+
+    egui::Window::new("HandScroll")
+        .anchor(egui::Align2::LEFT_BOTTOM, egui::Vec2::new(50.0, -150.0))
+        .collapsible(false)
+        .resizable(false)
+        .title_bar(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("◀").clicked() {
+                    scroll.first = scroll.first.saturating_sub(1);
+                    reorganize_hand_event.send(ReorganizeHandEvent);
+                }
+                // Clamped by the layout of the hand
+                if ui.button("▶").clicked() {
+                    scroll.first += 1;
+                    reorganize_hand_event.send(ReorganizeHandEvent);
+                }
+            });
+        });
+}
+
+/// Window with a single big button, `activated` when its key or gamepad button was pressed
 fn button_window(
     ctx: &egui::Context,
     text: &str,
     align: egui::Align2,
     offset: egui::Vec2,
+    activated: bool,
     on_click: impl FnOnce(),
 ) {
     egui::Window::new(egui::RichText::new(text).strong())
@@ -253,13 +425,13 @@ fn button_window(
         .resizable(false)
         .title_bar(false)
         .show(ctx, |ui| {
-            if ui.add(button(text)).clicked() {
+            if ui.add(button(text)).clicked() || activated {
                 on_click();
             }
         });
 }
 
-fn small_card_count(ui: &mut egui::Ui, player: &Player, current_color: Color) {
+fn small_card_count(ui: &mut egui::Ui, player: &Player, current_color: Color, settings: &Settings) {
     const CARD_WIDTH: f32 = 12.5;
     const CARD_HEIGHT: f32 = 19.25; // height = 1.54 * width for a uno card
     const CARD_PADDING: f32 = 2.0;
@@ -269,23 +441,20 @@ fn small_card_count(ui: &mut egui::Ui, player: &Player, current_color: Color) {
     let (mut rect, _response) = ui.allocate_exact_size(size, egui::Sense::hover());
     rect.set_width(CARD_WIDTH);
 
-    let card_color = if *player.is_playing {
-        let mut color = egui::Color32::WHITE;
-
-        for (uno_color, egui_color) in COLORS {
-            if uno_color == current_color {
-                color = egui_color;
-                break;
-            }
-        }
-
-        color
+    let card_color = if *player.is_playing && current_color != Color::Black {
+        settings.palette.color32(current_color)
+    } else if *player.is_playing {
+        egui::Color32::WHITE
     } else {
         egui::Color32::from_gray(150)
     };
+    let show_symbol = settings.colorblind_mode && *player.is_playing;
 
     for _ in 0..*player.hand_size {
         ui.painter().rect_filled(rect, 2.0, card_color);
+        if show_symbol {
+            paint_color_symbol(ui.painter(), rect, current_color);
+        }
         rect.set_center(rect.center() + egui::Vec2::new(CARD_WIDTH + CARD_PADDING, 0.0));
     }
 }
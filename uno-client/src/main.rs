@@ -1,17 +1,24 @@
 #![allow(clippy::type_complexity)]
 #![allow(clippy::too_many_arguments)]
 
+mod accessibility;
+mod chat;
+mod controls;
 mod game;
 mod menu;
+mod offline;
+mod settings;
+mod sounds;
 pub mod utils;
 
 use bevy::prelude::*;
 use bevy_egui::EguiPlugin;
-use naia_bevy_client::{Client, ClientConfig, Plugin as ClientPlugin};
+use naia_bevy_client::{ClientConfig, Plugin as ClientPlugin};
 use serde::{Deserialize, Serialize};
+pub use settings::Settings;
 use uno::{
     network::{shared_config, Channels, Protocol},
-    texts::{Language, Texts},
+    texts::Texts,
 };
 use utils::drag_and_drop::*;
 
@@ -35,13 +42,6 @@ pub struct SpriteSize {
 #[derive(Deref, DerefMut, Component)]
 pub struct PlayerId(pub Option<u64>);
 
-// Resources
-pub struct Settings {
-    username: String,
-    enable_animations: bool,
-    language: Language,
-}
-
 fn main() {
     let mut app = App::new();
 
@@ -52,6 +52,8 @@ fn main() {
         // vsync: false,
         ..WindowDescriptor::default()
     })
+    // Loaded before the plugins, some of them read the settings when they are built
+    .insert_resource(Settings::load())
     .add_state(GameState::Lobbies)
     .add_plugins(DefaultPlugins)
     .add_plugin(EguiPlugin)
@@ -63,16 +65,20 @@ fn main() {
     .add_plugin(utils::drag_and_drop::DragAndDropPlugin)
     .add_plugin(menu::MenuPlugin)
     .add_plugin(game::GamePlugin)
+    .add_plugin(chat::ChatPlugin)
+    .add_plugin(offline::OfflinePlugin)
+    .add_plugin(controls::ControlsPlugin)
+    .add_plugin(accessibility::AccessibilityPlugin)
+    .add_plugin(sounds::SoundsPlugin)
     .add_startup_system(setup)
     .add_system(utils::errors::display_error)
+    .add_system_to_stage(CoreStage::Last, settings::save_settings)
     // .add_system(animate_sprite_system)
-    .insert_resource(Settings {
-        username: String::from(""),
-        enable_animations: true,
-        language: Language::Francais,
-    })
     .insert_resource(PlayerId(None))
-    .insert_resource(Texts::get_all());
+    .insert_resource(Texts::builtin());
+
+    #[cfg(not(target_arch = "wasm32"))]
+    app.add_startup_system(load_locales);
 
     #[cfg(target_arch = "wasm32")]
     app.add_plugin(bevy_web_resizer::Plugin);
@@ -80,8 +86,21 @@ fn main() {
     app.run();
 }
 
-fn setup(mut commands: Commands, mut client: Client<Protocol, Channels>) {
+fn setup(mut commands: Commands) {
     commands.spawn_bundle(OrthographicCameraBundle::new_2d());
-    client.auth(uno::network::protocol::Uno::new());
-    client.connect("http://127.0.0.1:3478");
+}
+
+/// Add the locales of `assets/locales` to the builtin ones, a new translation only needs a
+/// `<code>.ron` file there
+#[cfg(not(target_arch = "wasm32"))]
+fn load_locales(mut texts: ResMut<Texts>) {
+    let locales_dir = bevy::asset::FileAssetIo::get_root_path()
+        .join("assets")
+        .join("locales");
+
+    if locales_dir.is_dir() {
+        for (path, err) in texts.load_dir(&locales_dir) {
+            warn!("Problem with the locale {}: {err}", path.display());
+        }
+    }
 }
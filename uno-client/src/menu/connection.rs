@@ -0,0 +1,203 @@
+use super::{LobbiesList, LobbyState};
+use crate::{
+    game::GameExitEvent, offline::OfflineGame, utils::errors::Error, GameState, PlayerId, Settings,
+};
+use bevy::prelude::*;
+use naia_bevy_client::{
+    events::{ConnectEvent, DisconnectEvent, MessageEvent, RejectEvent},
+    Client,
+};
+use uno::{
+    network::{
+        protocol::{Handshake, Username},
+        Channels, Protocol,
+    },
+    texts::{TextId, Texts},
+    username::validate_username,
+};
+
+pub const DEFAULT_SERVER_ADDRESS: &str = "http://127.0.0.1:3478";
+/// Number of servers kept in the recent servers list
+const MAX_RECENT_SERVERS: usize = 5;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ConnectionStatus {
+    Disconnected,
+    Connecting,
+    Connected,
+    /// The server refused the handshake
+    Rejected,
+    /// The server told us that our version of the game is out of date
+    Incompatible,
+    /// The connection dropped without the user asking for it
+    Lost,
+}
+
+// Ressources
+pub struct Connection {
+    /// Content of the address field
+    pub address: String,
+    /// Address of the last connection attempt, used to reconnect
+    pub server: Option<String>,
+    /// Most recent first
+    pub recent_servers: Vec<String>,
+    pub status: ConnectionStatus,
+    /// Version of the game run by the server, when it refused us for being incompatible
+    pub server_version: Option<String>,
+}
+
+impl FromWorld for Connection {
+    fn from_world(world: &mut World) -> Self {
+        let settings = world.get_resource::<Settings>();
+
+        Connection {
+            address: settings
+                .and_then(|settings| settings.server_address.clone())
+                .unwrap_or_else(|| DEFAULT_SERVER_ADDRESS.to_owned()),
+            server: None,
+            recent_servers: settings
+                .map(|settings| settings.recent_servers.clone())
+                .unwrap_or_default(),
+            status: ConnectionStatus::Disconnected,
+            server_version: None,
+        }
+    }
+}
+
+impl Connection {
+    /// Start connecting to a server, the address can omit the scheme
+    pub fn connect(&mut self, client: &mut Client<Protocol, Channels>, address: &str) {
+        let address = address.trim();
+        let url = if address.contains("://") {
+            address.to_owned()
+        } else {
+            format!("http://{address}")
+        };
+
+        client.auth(Handshake::new(env!("CARGO_PKG_VERSION").to_owned()));
+        client.connect(&url);
+
+        self.address = url.clone();
+        self.server = Some(url);
+        self.status = ConnectionStatus::Connecting;
+    }
+
+    pub fn disconnect(&mut self, client: &mut Client<Protocol, Channels>) {
+        if client.is_connected() {
+            client.disconnect();
+        }
+
+        self.status = ConnectionStatus::Disconnected;
+    }
+}
+
+pub fn connection_established(
+    mut connect_events: EventReader<ConnectEvent>,
+    mut client: Client<Protocol, Channels>,
+    mut connection: ResMut<Connection>,
+    mut lobby_state: ResMut<State<LobbyState>>,
+    mut settings: ResMut<Settings>,
+) {
+    for _ in connect_events.iter() {
+        connection.status = ConnectionStatus::Connected;
+
+        // The username is otherwise only sent once edited, the server starts with an empty one
+        if let Ok(username) = validate_username(&settings.username, &[]) {
+            client.send_message(Channels::Uno, &Username::new(username));
+        }
+
+        if let Some(server) = connection.server.clone() {
+            connection.recent_servers.retain(|recent| *recent != server);
+            connection.recent_servers.insert(0, server.clone());
+            connection.recent_servers.truncate(MAX_RECENT_SERVERS);
+
+            settings.server_address = Some(server);
+            settings.recent_servers = connection.recent_servers.clone();
+        }
+
+        if lobby_state.current() == &LobbyState::Unconnected {
+            lobby_state.set(LobbyState::LobbiesList).unwrap();
+        }
+    }
+}
+
+pub fn connection_rejected(
+    mut commands: Commands,
+    mut reject_events: EventReader<RejectEvent>,
+    mut connection: ResMut<Connection>,
+    settings: Res<Settings>,
+    texts: Res<Texts>,
+) {
+    for _ in reject_events.iter() {
+        connection.status = ConnectionStatus::Rejected;
+
+        commands.spawn().insert(Error {
+            message: texts.get(TextId::ConnectionRejected, &settings.language),
+        });
+    }
+}
+
+pub fn incompatible_version(
+    mut commands: Commands,
+    mut message_events: EventReader<MessageEvent<Protocol, Channels>>,
+    mut client: Client<Protocol, Channels>,
+    mut connection: ResMut<Connection>,
+    mut lobby_state: ResMut<State<LobbyState>>,
+    settings: Res<Settings>,
+    texts: Res<Texts>,
+) {
+    for MessageEvent(_, message) in message_events.iter() {
+        if let Protocol::IncompatibleVersion(incompatible) = message {
+            connection.disconnect(&mut client);
+            connection.status = ConnectionStatus::Incompatible;
+            connection.server_version = Some((*incompatible.server_version).clone());
+
+            commands.spawn().insert(Error {
+                message: texts.format(
+                    TextId::IncompatibleVersion,
+                    &settings.language,
+                    &[("version", incompatible.server_version.as_str())],
+                ),
+            });
+
+            if lobby_state.current() != &LobbyState::Unconnected {
+                lobby_state.set(LobbyState::Unconnected).unwrap();
+            }
+        }
+    }
+}
+
+/// Go back to the connect screen, leaving the online game if there is one
+pub fn connection_lost(
+    mut disconnect_events: EventReader<DisconnectEvent>,
+    mut connection: ResMut<Connection>,
+    mut lobby_state: ResMut<State<LobbyState>>,
+    mut lobbies: ResMut<LobbiesList>,
+    mut player_id: ResMut<PlayerId>,
+    mut game_exit_event: EventWriter<GameExitEvent>,
+    game_state: Res<State<GameState>>,
+    offline_game: Option<Res<OfflineGame>>,
+) {
+    for _ in disconnect_events.iter() {
+        if !matches!(
+            connection.status,
+            ConnectionStatus::Disconnected | ConnectionStatus::Incompatible
+        ) {
+            connection.status = ConnectionStatus::Lost;
+        }
+
+        lobbies.clear();
+
+        if offline_game.is_none() {
+            **player_id = None;
+
+            if game_state.current() != &GameState::Lobbies {
+                game_exit_event.send(GameExitEvent);
+            }
+        }
+
+        if lobby_state.current() != &LobbyState::Unconnected {
+            lobby_state.set(LobbyState::Unconnected).unwrap();
+        }
+    }
+}
@@ -2,25 +2,34 @@ use super::{LobbiesList, LobbyState};
 use crate::{
     game::{ExtraMessageEvent, StartGameEvent},
     utils::errors::Error,
-    PlayerId,
+    PlayerId, Settings,
 };
 use bevy::prelude::*;
 use naia_bevy_client::events::MessageEvent;
-use uno::network::{protocol::YourPlayerId, Channels, Protocol};
+use uno::{
+    network::{protocol::YourPlayerId, Channels, Protocol},
+    texts::Texts,
+};
 
 pub fn execute_packets(
     mut commands: Commands,
     mut lobby_state: ResMut<State<LobbyState>>,
     mut lobbies: ResMut<LobbiesList>,
-    mut player_id: ResMut<PlayerId>,
     mut start_game_event: EventWriter<StartGameEvent>,
     mut message_events: EventReader<MessageEvent<Protocol, Channels>>,
     mut extra_message_events: EventWriter<ExtraMessageEvent>,
+    settings: Res<Settings>,
+    texts: Res<Texts>,
 ) {
-    for MessageEvent(_, protocol) in message_events.iter() {
+    for MessageEvent(channel, protocol) in message_events.iter() {
+        // Chat messages are handled by the chat, whatever the state of the game
+        if let Channels::Chat = channel {
+            continue;
+        }
+
         match protocol {
             Protocol::StartGame(_) => {
-                println!("in start game");
+                info!("Starting the game");
                 if let LobbyState::InLobby(_) = lobby_state.current() {
                     start_game_event.send(StartGameEvent);
                     break;
@@ -32,7 +41,11 @@ pub fn execute_packets(
                 }
             }
             Protocol::JoinLobby(lobby) => {
-                lobby_state.set(LobbyState::InLobby(*lobby.id)).unwrap();
+                // Playing again brings the players back to the lobby they were already in
+                let in_lobby = LobbyState::InLobby(*lobby.id);
+                if lobby_state.current() != &in_lobby {
+                    lobby_state.set(in_lobby).unwrap();
+                }
             }
             Protocol::LeaveLobby(_) => {
                 lobby_state.set(LobbyState::LobbiesList).unwrap();
@@ -42,12 +55,26 @@ pub fn execute_packets(
                     message: (*error.error).clone(),
                 });
             }
-            Protocol::YourPlayerId(YourPlayerId { id }) => **player_id = Some(**id),
+            Protocol::UsernameRejected(rejected) => {
+                commands.spawn().insert(Error {
+                    message: texts.get(rejected.error.text_id(), &settings.language),
+                });
+            }
             protocol => {
-                println!("receiving extra message");
+                debug!("Forwarding a message to the game");
                 extra_message_events.send(ExtraMessageEvent(protocol.clone()));
                 // return;
             }
         };
     }
 }
+
+/// The server sends our player id in a component only replicated to us
+pub fn player_id_received(
+    mut player_id: ResMut<PlayerId>,
+    your_player_id_query: Query<&YourPlayerId, Added<YourPlayerId>>,
+) {
+    for YourPlayerId { id } in your_player_id_query.iter() {
+        **player_id = Some(**id);
+    }
+}
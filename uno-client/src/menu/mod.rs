@@ -1,3 +1,4 @@
+pub mod connection;
 pub mod lobbies;
 mod ui;
 
@@ -21,7 +22,13 @@ impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(LobbiesList(vec![]))
             .insert_resource(Option::<Lobby>::None)
-            .add_state(LobbyState::LobbiesList)
+            .init_resource::<connection::Connection>()
+            .add_state(LobbyState::Unconnected)
+            .add_system(connection::connection_established)
+            .add_system(connection::connection_rejected)
+            .add_system(connection::incompatible_version)
+            .add_system(connection::connection_lost)
+            .add_system(lobbies::player_id_received)
             .add_system_set(
                 SystemSet::new()
                     .with_run_criteria(run_if_connected)
@@ -34,8 +41,11 @@ impl Plugin for MenuPlugin {
                     .with_run_criteria(run_if_connected)
                     .with_system(ui::lobby_panel),
             )
-            .add_system_set(
-                SystemSet::new().with_run_criteria(run_if_not_connected), // .with_system(ui::unconnected_panel),
+            .add_system_set_to_stage(
+                CoreStage::PostUpdate,
+                SystemSet::new()
+                    .with_run_criteria(run_if_not_connected)
+                    .with_system(ui::unconnected_panel),
             );
     }
 }
@@ -1,5 +1,11 @@
-use super::LobbyState;
-use crate::{utils::errors::Error, PlayerId, Settings};
+use super::{
+    connection::{Connection, ConnectionStatus},
+    LobbyState,
+};
+use crate::{
+    accessibility::Palette, controls::ControlsWindow, game::CardThemes, offline::OfflineSetup,
+    utils::errors::Error, PlayerId, Settings,
+};
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContext};
 use naia_bevy_client::Client;
@@ -8,16 +14,19 @@ use uno::{
         protocol::{self, Lobby, Player},
         Channels, Protocol,
     },
-    texts::{Language, TextId, Texts},
+    texts::{TextId, Texts},
+    username::validate_username,
 };
 
 pub fn settings_panel(
     mut settings: ResMut<Settings>,
     mut client: Client<Protocol, Channels>,
     mut egui_context: ResMut<EguiContext>,
+    mut controls_window: ResMut<ControlsWindow>,
+    card_themes: Res<CardThemes>,
     texts: Res<Texts>,
 ) {
-    let language = settings.language;
+    let language = &settings.language.clone();
 
     egui::TopBottomPanel::top("Settings").show(egui_context.ctx_mut(), |ui| {
         ui.vertical_centered(|ui| {
@@ -31,14 +40,22 @@ pub fn settings_panel(
         ui.separator();
 
         ui.horizontal(|ui| {
-            if ui.text_edit_singleline(&mut settings.username).changed() {
-                client.send_message(
-                    Channels::Uno,
-                    &protocol::Username::new(settings.username.clone()),
-                );
+            let response = ui.text_edit_singleline(&mut settings.username);
+            // The server has the final word (blocklist, duplicates), this only catches the obvious mistakes
+            let validation = validate_username(&settings.username, &[]);
+
+            // Only send the username once the user is done editing it, not on every keystroke
+            if response.lost_focus() {
+                if let Ok(username) = &validation {
+                    client.send_message(Channels::Uno, &protocol::Username::new(username.clone()));
+                }
             }
             ui.label(texts.get(TextId::Username, language));
 
+            if let Err(error) = &validation {
+                ui.colored_label(egui::Color32::RED, texts.get(error.text_id(), language));
+            }
+
             ui.separator();
 
             ui.checkbox(
@@ -48,19 +65,76 @@ pub fn settings_panel(
 
             ui.separator();
 
+            ui.checkbox(&mut settings.mute, texts.get(TextId::Mute, language));
+            ui.add_enabled(
+                !settings.mute,
+                egui::Slider::new(&mut settings.volume, 0.0..=1.0)
+                    .show_value(false)
+                    .text(texts.get(TextId::Volume, language)),
+            );
+            ui.add_enabled(
+                !settings.mute,
+                egui::Slider::new(&mut settings.music_volume, 0.0..=1.0)
+                    .show_value(false)
+                    .text(texts.get(TextId::Music, language)),
+            );
+
+            ui.separator();
+
             egui::ComboBox::from_label(texts.get(TextId::Language, language))
-                .selected_text(format!("{}", settings.language))
+                .selected_text(texts.language_name(language))
                 .show_ui(ui, |ui| {
-                    ui.selectable_value(
-                        &mut settings.language,
-                        Language::Francais,
-                        Language::Francais.to_string(),
-                    );
-                    ui.selectable_value(
-                        &mut settings.language,
-                        Language::English,
-                        Language::English.to_string(),
-                    );
+                    for (available_language, name) in texts.languages() {
+                        ui.selectable_value(
+                            &mut settings.language,
+                            available_language.clone(),
+                            name,
+                        );
+                    }
+                });
+
+            ui.separator();
+
+            if ui.button(texts.get(TextId::Controls, language)).clicked() {
+                controls_window.open = !controls_window.open;
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.checkbox(
+                &mut settings.colorblind_mode,
+                texts.get(TextId::ColorblindMode, language),
+            );
+
+            ui.separator();
+
+            ui.checkbox(
+                &mut settings.high_contrast,
+                texts.get(TextId::HighContrast, language),
+            );
+
+            ui.separator();
+
+            egui::ComboBox::from_label(texts.get(TextId::Palette, language))
+                .selected_text(texts.get(settings.palette.text_id(), language))
+                .show_ui(ui, |ui| {
+                    for palette in Palette::ALL {
+                        ui.selectable_value(
+                            &mut settings.palette,
+                            palette,
+                            texts.get(palette.text_id(), language),
+                        );
+                    }
+                });
+
+            ui.separator();
+
+            egui::ComboBox::from_label(texts.get(TextId::CardTheme, language))
+                .selected_text(card_themes.theme_name(&settings.card_theme.clone()))
+                .show_ui(ui, |ui| {
+                    for (id, name) in card_themes.themes() {
+                        ui.selectable_value(&mut settings.card_theme, id.to_owned(), name);
+                    }
                 });
         })
     });
@@ -71,20 +145,26 @@ pub fn lobby_panel(
     mut client: Client<Protocol, Channels>,
     mut egui_context: ResMut<EguiContext>,
     settings: Res<Settings>,
-    lobby_state: ResMut<State<LobbyState>>,
+    mut lobby_state: ResMut<State<LobbyState>>,
+    mut connection: ResMut<Connection>,
     lobbies_query: Query<&Lobby>,
     players_query: Query<&Player>,
     player_id: Res<PlayerId>,
+    mut offline_setup: ResMut<OfflineSetup>,
     texts: Res<Texts>,
 ) {
-    let language = settings.language;
+    if offline_setup.open {
+        return;
+    }
+
+    let language = &settings.language;
 
     let window = egui::Window::new(texts.get(TextId::UnoTitle, language))
         .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
         .collapsible(false)
         .resizable(false);
 
-    match lobby_state.current() {
+    match lobby_state.current().clone() {
         LobbyState::LobbiesList => window.show(egui_context.ctx_mut(), |ui| {
             ui.vertical_centered(|ui| {
                 ui.heading(texts.get(TextId::LobbiesTitle, language));
@@ -111,9 +191,11 @@ pub fn lobby_panel(
                                     ui.label(format!("{}/10", *lobby.number_of_players));
 
                                     if ui.button(texts.get(TextId::JoinLobby, language)).clicked() {
-                                        if settings.username.trim().is_empty() {
+                                        if let Err(error) =
+                                            validate_username(&settings.username, &[])
+                                        {
                                             commands.spawn().insert(Error {
-                                                message: texts.get(TextId::EnterUsername, language),
+                                                message: texts.get(error.text_id(), language),
                                             });
                                         } else {
                                             client.send_message(
@@ -137,6 +219,18 @@ pub fn lobby_panel(
                 {
                     client.send_message(Channels::Uno, &protocol::CreateLobby::new());
                 }
+
+                if ui
+                    .button(texts.get(TextId::OfflineGame, language))
+                    .clicked()
+                {
+                    offline_setup.open = true;
+                }
+
+                if ui.button(texts.get(TextId::Disconnect, language)).clicked() {
+                    connection.disconnect(&mut client);
+                    lobby_state.set(LobbyState::Unconnected).unwrap();
+                }
             });
         }),
         LobbyState::InLobby(lobby_id) => window.show(egui_context.ctx_mut(), |ui| {
@@ -166,7 +260,7 @@ pub fn lobby_panel(
 
             ui.vertical_centered(|ui| {
                 if ui.button(texts.get(TextId::LeaveLobby, language)).clicked() {
-                    client.send_message(Channels::Uno, &protocol::LeaveLobby::new(*lobby_id));
+                    client.send_message(Channels::Uno, &protocol::LeaveLobby::new(lobby_id));
                 }
 
                 if ui.button(texts.get(TextId::StartGame, language)).clicked() {
@@ -178,24 +272,118 @@ pub fn lobby_panel(
     };
 }
 
-/*
 pub fn unconnected_panel(
-    commands: Commands,
+    mut client: Client<Protocol, Channels>,
     mut egui_context: ResMut<EguiContext>,
-    lobby_state: ResMut<State<LobbyState>>,
+    mut connection: ResMut<Connection>,
+    mut offline_setup: ResMut<OfflineSetup>,
+    settings: Res<Settings>,
+    texts: Res<Texts>,
 ) {
-    egui::Window::new("Unconnected")
+    if offline_setup.open {
+        return;
+    }
+
+    let language = &settings.language;
+    let connecting = connection.status == ConnectionStatus::Connecting;
+
+    egui::Window::new(texts.get(TextId::UnoTitle, language))
         .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
         .collapsible(false)
         .resizable(false)
         .show(egui_context.ctx_mut(), |ui| {
             ui.vertical_centered(|ui| {
-                ui.heading("You're not connected to the server");
-                ui.add_space(10.0);
-                if ui.button("Reconnect").clicked() {
-                    // connect_to_server(commands, lobby_state);
+                ui.heading(texts.get(TextId::Server, language));
+            });
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                let response = ui.add_enabled(
+                    !connecting,
+                    egui::TextEdit::singleline(&mut connection.address),
+                );
+                let submitted = response.lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+
+                if (ui
+                    .add_enabled(
+                        !connecting,
+                        egui::Button::new(texts.get(TextId::Connect, language)),
+                    )
+                    .clicked()
+                    || submitted)
+                    && !connecting
+                {
+                    let address = connection.address.clone();
+                    connection.connect(&mut client, &address);
+                }
+            });
+
+            match connection.status {
+                ConnectionStatus::Connecting => {
+                    ui.label(format!("⏳ {}", texts.get(TextId::Connecting, language)));
+                }
+                ConnectionStatus::Rejected => {
+                    ui.colored_label(
+                        egui::Color32::RED,
+                        texts.get(TextId::ConnectionRejected, language),
+                    );
+                }
+                ConnectionStatus::Incompatible => {
+                    ui.colored_label(
+                        egui::Color32::RED,
+                        texts.format(
+                            TextId::IncompatibleVersion,
+                            language,
+                            &[(
+                                "version",
+                                connection.server_version.as_deref().unwrap_or_default(),
+                            )],
+                        ),
+                    );
+                }
+                ConnectionStatus::Lost => {
+                    ui.colored_label(
+                        egui::Color32::RED,
+                        texts.get(TextId::ConnectionLost, language),
+                    );
+                }
+                ConnectionStatus::Disconnected | ConnectionStatus::Connected => {
+                    ui.label(texts.get(TextId::NotConnected, language));
+                }
+            }
+
+            if let (Some(server), ConnectionStatus::Rejected | ConnectionStatus::Lost) =
+                (connection.server.clone(), connection.status)
+            {
+                if ui.button(texts.get(TextId::Reconnect, language)).clicked() {
+                    connection.connect(&mut client, &server);
+                }
+            }
+
+            if !connection.recent_servers.is_empty() {
+                ui.separator();
+                ui.label(texts.get(TextId::RecentServers, language));
+
+                for server in connection.recent_servers.clone() {
+                    if ui
+                        .add_enabled(!connecting, egui::Button::new(server.as_str()))
+                        .clicked()
+                    {
+                        connection.connect(&mut client, &server);
+                    }
+                }
+            }
+
+            ui.separator();
+
+            ui.vertical_centered(|ui| {
+                if ui
+                    .button(texts.get(TextId::OfflineGame, language))
+                    .clicked()
+                {
+                    offline_setup.open = true;
                 }
             });
         });
 }
-*/
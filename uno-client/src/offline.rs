@@ -0,0 +1,530 @@
+use crate::{
+    game::{
+        CallCounterUno, CallUno, ChooseColor, DrawCard, GameActionEvent, Hand, HandItem,
+        StartGameEvent,
+    },
+    GameState, PlayerId, Settings,
+};
+use bevy::{ecs::schedule::ShouldRun, prelude::*};
+use bevy_egui::{egui, EguiContext};
+use naia_bevy_client::events::MessageEvent;
+use uno::{
+    card::Color,
+    network::{
+        protocol::{CurrentColor, GameAction, GameActionKind, Player, TableState},
+        Channels, Protocol,
+    },
+    player::PlayerState,
+    rules::GameRules,
+    texts::{TextId, Texts},
+    username::validate_username,
+};
+
+/// Same limit as the lobbies of the server
+const MAX_SEATS: usize = 10;
+/// Time a bot waits before each of its actions
+const BOT_DELAY_S: f32 = 1.0;
+/// Time a bot leaves a player to call Uno before countering them
+const BOT_COUNTER_UNO_DELAY_S: f32 = 2.5;
+
+pub struct OfflinePlugin;
+
+struct SeatSetup {
+    username: String,
+    bot: bool,
+}
+
+// Ressources
+/// Seats of the offline game being configured, the window is shown while it is open
+#[derive(Default)]
+pub struct OfflineSetup {
+    pub open: bool,
+    seats: Vec<SeatSetup>,
+}
+
+/// Game played without server, the rules run in the client and the messages of the game are
+/// delivered as if they came from the server
+pub struct OfflineGame {
+    game: GameRules,
+    bots: Vec<bool>,
+    /// Seat whose hand is shown
+    viewing: usize,
+    /// Seat waiting for its player to take the device
+    handover: Option<usize>,
+    bot_timer: Timer,
+    /// Time the current player has taken to call Uno
+    uno_wait: f32,
+    /// Id given by the server, restored when leaving the game
+    online_player_id: Option<u64>,
+}
+
+impl OfflineGame {
+    fn is_bot(&self, seat: usize) -> bool {
+        self.bots[seat]
+    }
+
+    /// Seat given the device when a round starts, and whether it has to be handed over to it
+    fn first_viewer(game: &GameRules, bots: &[bool]) -> (usize, Option<usize>) {
+        // The first player to take the device is the first one to play, unless a bot starts
+        let viewing = if bots[game.current_seat()] {
+            bots.iter().position(|bot| !bot).unwrap()
+        } else {
+            game.current_seat()
+        };
+        let humans = bots.iter().filter(|bot| !**bot).count();
+
+        (viewing, if humans > 1 { Some(viewing) } else { None })
+    }
+}
+
+// Components
+/// Marks the entities spawned by the offline game in place of the ones the server replicates
+#[derive(Component)]
+pub struct OfflineEntity;
+
+impl Plugin for OfflinePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OfflineSetup>()
+            .add_system(apply_game_actions)
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(run_if_offline)
+                    .with_system(deliver_messages)
+                    .with_system(sync_entities),
+            )
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(run_if_playing_offline)
+                    .with_system(play_bots)
+                    .with_system(start_handover),
+            )
+            .add_system_set_to_stage(
+                CoreStage::PostUpdate,
+                SystemSet::new()
+                    .with_run_criteria(run_if_playing_offline)
+                    .with_system(handover_screen),
+            )
+            .add_system_set_to_stage(
+                CoreStage::PostUpdate,
+                SystemSet::new().with_system(setup_window),
+            );
+    }
+}
+
+fn run_if_offline(offline_game: Option<Res<OfflineGame>>) -> ShouldRun {
+    if offline_game.is_some() {
+        ShouldRun::Yes
+    } else {
+        ShouldRun::No
+    }
+}
+
+fn run_if_playing_offline(
+    offline_game: Option<Res<OfflineGame>>,
+    game_state: Res<State<GameState>>,
+) -> ShouldRun {
+    if offline_game.is_some() && game_state.current() == &GameState::Game {
+        ShouldRun::Yes
+    } else {
+        ShouldRun::No
+    }
+}
+
+fn setup_window(
+    mut commands: Commands,
+    mut egui_context: ResMut<EguiContext>,
+    mut setup: ResMut<OfflineSetup>,
+    mut start_game_event: EventWriter<StartGameEvent>,
+    mut player_id: ResMut<PlayerId>,
+    game_state: Res<State<GameState>>,
+    settings: Res<Settings>,
+    texts: Res<Texts>,
+) {
+    if !setup.open || game_state.current() != &GameState::Lobbies {
+        return;
+    }
+
+    let language = &settings.language;
+
+    if setup.seats.is_empty() {
+        let username = validate_username(&settings.username, &[])
+            .unwrap_or_else(|_| format!("{} 1", texts.get(TextId::Player, language)));
+
+        setup.seats = vec![
+            SeatSetup {
+                username,
+                bot: false,
+            },
+            SeatSetup {
+                username: format!("{} 2", texts.get(TextId::Bot, language)),
+                bot: true,
+            },
+        ];
+    }
+
+    let mut open = true;
+    let mut start = false;
+
+    egui::Window::new(texts.get(TextId::OfflineGame, language))
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .collapsible(false)
+        .resizable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            let seats_count = setup.seats.len();
+            let mut removed = None;
+
+            for (i, seat) in setup.seats.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut seat.username);
+                    ui.selectable_value(&mut seat.bot, false, texts.get(TextId::Human, language));
+                    ui.selectable_value(&mut seat.bot, true, texts.get(TextId::Bot, language));
+
+                    if seats_count > 2 && ui.button("✖").clicked() {
+                        removed = Some(i);
+                    }
+                });
+
+                if let Err(error) = validate_username(&seat.username, &[]) {
+                    ui.colored_label(egui::Color32::RED, texts.get(error.text_id(), language));
+                }
+            }
+
+            if let Some(i) = removed {
+                setup.seats.remove(i);
+            }
+
+            ui.separator();
+
+            ui.vertical_centered(|ui| {
+                if setup.seats.len() < MAX_SEATS
+                    && ui.button(texts.get(TextId::AddPlayer, language)).clicked()
+                {
+                    let number = setup.seats.len() + 1;
+                    setup.seats.push(SeatSetup {
+                        username: format!("{} {number}", texts.get(TextId::Bot, language)),
+                        bot: true,
+                    });
+                }
+
+                let usernames = setup
+                    .seats
+                    .iter()
+                    .map(|seat| validate_username(&seat.username, &[]))
+                    .collect::<Result<Vec<_>, _>>();
+                let valid = match &usernames {
+                    Ok(usernames) => {
+                        usernames
+                            .iter()
+                            .enumerate()
+                            .all(|(i, username)| !usernames[..i].contains(username))
+                            && setup.seats.iter().any(|seat| !seat.bot)
+                    }
+                    Err(_) => false,
+                };
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            valid,
+                            egui::Button::new(texts.get(TextId::StartGame, language)),
+                        )
+                        .clicked()
+                    {
+                        start = true;
+                    }
+
+                    if ui.button(texts.get(TextId::Cancel, language)).clicked() {
+                        open = false;
+                    }
+                });
+            });
+        });
+
+    if start {
+        let usernames = setup
+            .seats
+            .iter()
+            .filter_map(|seat| validate_username(&seat.username, &[]).ok())
+            .collect::<Vec<_>>();
+        let bots = setup.seats.iter().map(|seat| seat.bot).collect::<Vec<_>>();
+        let game = GameRules::new(usernames);
+
+        for seat in &game.seats {
+            commands
+                .spawn()
+                .insert(Player::new(seat.id, None, seat.player.username.clone(), 0))
+                .insert(OfflineEntity);
+        }
+        commands
+            .spawn()
+            .insert(CurrentColor::new(game.current_color()))
+            .insert(OfflineEntity);
+        commands
+            .spawn()
+            .insert(TableState::default())
+            .insert(OfflineEntity);
+
+        let (viewing, handover) = OfflineGame::first_viewer(&game, &bots);
+
+        let online_player_id = **player_id;
+        **player_id = Some(game.seats[viewing].id);
+
+        commands.insert_resource(OfflineGame {
+            game,
+            bots,
+            viewing,
+            handover,
+            bot_timer: Timer::from_seconds(BOT_DELAY_S, true),
+            uno_wait: 0.0,
+            online_player_id,
+        });
+
+        start_game_event.send(StartGameEvent);
+        open = false;
+    }
+
+    if !open {
+        *setup = OfflineSetup::default();
+    }
+}
+
+/// Play the actions of the player at the device
+fn apply_game_actions(
+    mut commands: Commands,
+    mut game_action_events: EventReader<GameActionEvent>,
+    mut start_game_event: EventWriter<StartGameEvent>,
+    mut offline_game: Option<ResMut<OfflineGame>>,
+    mut player_id: ResMut<PlayerId>,
+    offline_entities: Query<Entity, With<OfflineEntity>>,
+) {
+    for GameActionEvent(action) in game_action_events.iter() {
+        let offline = match &mut offline_game {
+            Some(offline) => offline,
+            None => continue,
+        };
+
+        if *action.kind == GameActionKind::ExitGame {
+            for entity in offline_entities.iter() {
+                commands.entity(entity).despawn();
+            }
+
+            **player_id = offline.online_player_id;
+            commands.remove_resource::<OfflineGame>();
+            return;
+        }
+
+        if *action.kind == GameActionKind::PlayAgain {
+            if offline.game.is_over() {
+                offline.game = offline.game.next_round();
+                let (viewing, handover) = OfflineGame::first_viewer(&offline.game, &offline.bots);
+                offline.viewing = viewing;
+                offline.handover = handover;
+                offline.uno_wait = 0.0;
+                **player_id = Some(offline.game.seats[viewing].id);
+
+                start_game_event.send(StartGameEvent);
+            }
+            continue;
+        }
+
+        // Nobody is supposed to play while the device is handed over
+        if offline.handover.is_none() {
+            let seat = offline.viewing;
+            offline.game.apply(seat, action);
+        }
+    }
+}
+
+/// Only the player at the device gets the messages, the other ones get a snapshot when they
+/// take it
+fn deliver_messages(
+    mut offline_game: ResMut<OfflineGame>,
+    mut message_events: EventWriter<MessageEvent<Protocol, Channels>>,
+) {
+    let viewing = offline_game.viewing;
+
+    for (seat, message) in offline_game.game.messages() {
+        if seat == viewing {
+            message_events.send(MessageEvent(Channels::Uno, message));
+        }
+    }
+}
+
+/// Keep the components the server would replicate up to date
+fn sync_entities(
+    offline_game: Res<OfflineGame>,
+    mut players_query: Query<&mut Player, With<OfflineEntity>>,
+    mut current_color_query: Query<&mut CurrentColor, With<OfflineEntity>>,
+    mut table_state_query: Query<&mut TableState, With<OfflineEntity>>,
+) {
+    let game = &offline_game.game;
+
+    for mut network_player in players_query.iter_mut() {
+        if let Some(seat) = game.seats.iter().find(|seat| seat.id == *network_player.id) {
+            *network_player.hand_size = seat.player.hand.len();
+            *network_player.score = seat.player.score;
+            *network_player.is_playing = seat.player.is_playing;
+        }
+    }
+
+    for mut current_color in current_color_query.iter_mut() {
+        *current_color.color = game.current_color() as u8;
+    }
+
+    for mut table_state in table_state_query.iter_mut() {
+        *table_state.reverse_turn = game.reverse_turn();
+        *table_state.draw_pile_size = game.draw_pile_size();
+        *table_state.discard_size = game.discard_size();
+        *table_state.current_player = game.seats[game.current_seat()].id;
+    }
+}
+
+fn play_bots(time: Res<Time>, mut offline_game: ResMut<OfflineGame>) {
+    let offline = &mut *offline_game;
+    let seat = offline.game.current_seat();
+
+    if offline.game.is_over() {
+        return;
+    }
+
+    // Bots are quick to notice a forgotten Uno
+    if !offline.is_bot(seat) && offline.game.waiting_for_uno(seat) {
+        offline.uno_wait += time.delta_seconds();
+
+        if offline.uno_wait >= BOT_COUNTER_UNO_DELAY_S {
+            if let Some(bot) = offline.bots.iter().position(|bot| *bot) {
+                offline.game.apply(bot, &GameAction::counter_uno());
+            }
+        }
+    } else {
+        offline.uno_wait = 0.0;
+    }
+
+    if !offline.is_bot(seat) || !offline.bot_timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let game = &offline.game;
+    let player = &game.seats[seat].player;
+
+    let action = if game.choosing_color(seat) {
+        GameAction::choose_color(favorite_color(player))
+    } else if game.waiting_for_uno(seat) {
+        GameAction::uno()
+    } else if player.state == PlayerState::DrawingCard {
+        GameAction::draw_card()
+    } else if let Some(&card) = player
+        .hand
+        .iter()
+        .find(|card| card.can_be_played(game.top_discard(), game.current_color()))
+    {
+        GameAction::play_card(card)
+    } else {
+        return;
+    };
+
+    offline.game.apply(seat, &action);
+}
+
+/// Color of which the player has the most cards
+fn favorite_color(player: &uno::Player) -> Color {
+    [Color::Yellow, Color::Red, Color::Blue, Color::Green]
+        .into_iter()
+        .max_by_key(|&color| {
+            player
+                .hand
+                .iter()
+                .filter(|card| card.color == color)
+                .count()
+        })
+        .unwrap()
+}
+
+/// Hide the table when the turn goes to another player sharing the device
+fn start_handover(mut offline_game: ResMut<OfflineGame>) {
+    let seat = offline_game.game.current_seat();
+
+    if offline_game.handover.is_none()
+        && !offline_game.game.is_over()
+        && !offline_game.is_bot(seat)
+        && seat != offline_game.viewing
+    {
+        offline_game.handover = Some(seat);
+    }
+}
+
+fn handover_screen(
+    mut commands: Commands,
+    mut egui_context: ResMut<EguiContext>,
+    mut offline_game: ResMut<OfflineGame>,
+    mut hand: ResMut<Hand>,
+    mut player_id: ResMut<PlayerId>,
+    mut message_events: EventWriter<MessageEvent<Protocol, Channels>>,
+    hand_query: Query<Entity, With<HandItem>>,
+    prompts_query: Query<
+        Entity,
+        Or<(
+            With<ChooseColor>,
+            With<CallUno>,
+            With<CallCounterUno>,
+            With<DrawCard>,
+        )>,
+    >,
+    settings: Res<Settings>,
+    texts: Res<Texts>,
+) {
+    let seat = match offline_game.handover {
+        Some(seat) => seat,
+        None => return,
+    };
+
+    // Whatever was waiting for the previous player stays hidden
+    for entity in prompts_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let language = &settings.language;
+    let mut ready = false;
+
+    egui::CentralPanel::default()
+        .frame(egui::Frame::none().fill(egui::Color32::from_gray(20)))
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.vertical_centered(|ui| {
+                ui.add_space(ui.available_height() / 3.0);
+                ui.label(
+                    egui::RichText::new(texts.format(
+                        TextId::HandOver,
+                        language,
+                        &[("player", &offline_game.game.seats[seat].player.username)],
+                    ))
+                    .size(40.0)
+                    .strong(),
+                );
+                ui.add_space(20.0);
+
+                ready = ui.button(texts.get(TextId::Ready, language)).clicked();
+            });
+        });
+
+    if !ready {
+        return;
+    }
+
+    // The table is rebuilt from the snapshot of the new player
+    for entity in hand_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    hand.size = 0;
+
+    offline_game.viewing = seat;
+    offline_game.handover = None;
+    **player_id = Some(offline_game.game.seats[seat].id);
+
+    message_events.send(MessageEvent(
+        Channels::Uno,
+        Protocol::GameSnapshot(offline_game.game.snapshot(seat)),
+    ));
+    for prompt in offline_game.game.prompts(seat) {
+        message_events.send(MessageEvent(Channels::Uno, prompt));
+    }
+}
@@ -0,0 +1,203 @@
+use crate::{
+    accessibility::Palette,
+    controls::Bindings,
+    game::{HandSort, DEFAULT_THEME},
+};
+use bevy::{app::AppExit, prelude::*};
+use serde::{Deserialize, Serialize};
+use uno::texts::Language;
+
+/// Name of the settings file in the config directory, or key in the local storage on the web
+const SETTINGS_NAME: &str = "settings.ron";
+/// Time the settings have to stay the same before they are saved, so that typing in a text
+/// field doesn't write them at each keystroke
+const SAVE_DELAY_S: f64 = 1.0;
+
+/// Settings of the player, saved shortly after they change. Fields missing from the saved file,
+/// because it was written by an older version, get their default value
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub username: String,
+    pub enable_animations: bool,
+    pub language: Language,
+    /// Address of the last server the player connected to
+    pub server_address: Option<String>,
+    /// Most recent first
+    pub recent_servers: Vec<String>,
+    pub bindings: Bindings,
+    /// Draw a symbol for each color on the cards
+    pub colorblind_mode: bool,
+    pub palette: Palette,
+    pub high_contrast: bool,
+    /// Id of the card theme, the name of its manifest
+    pub card_theme: String,
+    pub hand_sort: HandSort,
+    /// Volume of the sounds, from 0 to 1
+    pub volume: f32,
+    /// Volume of the music, from 0 to 1
+    pub music_volume: f32,
+    pub mute: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            username: String::new(),
+            enable_animations: true,
+            language: system_language(),
+            server_address: None,
+            recent_servers: vec![],
+            bindings: Bindings::default(),
+            colorblind_mode: false,
+            palette: Palette::Classic,
+            high_contrast: false,
+            card_theme: DEFAULT_THEME.to_owned(),
+            hand_sort: HandSort::Manual,
+            volume: 0.8,
+            music_volume: 0.5,
+            mute: false,
+        }
+    }
+}
+
+impl Settings {
+    /// Read the saved settings, or the default ones if there are none or they can't be read
+    pub fn load() -> Settings {
+        let content = match storage::read() {
+            Some(content) => content,
+            None => return Settings::default(),
+        };
+
+        ron::de::from_str(&content).unwrap_or_else(|err| {
+            warn!("Couldn't read the settings, using the default ones: {err}");
+            Settings::default()
+        })
+    }
+
+    pub fn save(&self) {
+        match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(content) => storage::write(&content),
+            Err(err) => error!("Couldn't serialize the settings: {err}"),
+        }
+    }
+}
+
+/// Language of the system, the texts fall back to English if there is no locale for it
+fn system_language() -> Language {
+    sys_locale::get_locale().map_or_else(Language::english, |locale| Language::from_locale(&locale))
+}
+
+#[derive(Default)]
+pub struct SettingsSave {
+    /// Settings as they were last saved or loaded
+    saved: Option<Settings>,
+    /// Settings waiting to be saved, with the time they last changed
+    pending: Option<(Settings, f64)>,
+}
+
+/// Save the settings once they haven't changed for `SAVE_DELAY_S`, or right away when the app
+/// exits. Text fields mark the settings as changed every frame, so they are compared instead of
+/// relying on change detection alone
+pub fn save_settings(
+    settings: Res<Settings>,
+    time: Res<Time>,
+    mut app_exit_events: EventReader<AppExit>,
+    mut save: Local<SettingsSave>,
+) {
+    let now = time.seconds_since_startup();
+    let save = &mut *save;
+
+    let saved = match &save.saved {
+        Some(saved) => saved,
+        // Just loaded, nothing new to save
+        None => {
+            save.saved = Some(settings.clone());
+            return;
+        }
+    };
+
+    if settings.is_changed()
+        && !matches!(&save.pending, Some((pending, _)) if pending == &*settings)
+    {
+        save.pending = if saved == &*settings {
+            None
+        } else {
+            Some((settings.clone(), now))
+        };
+    }
+
+    let exiting = app_exit_events.iter().count() > 0;
+    let due = match &save.pending {
+        Some((_, changed_at)) => exiting || now - changed_at >= SAVE_DELAY_S,
+        None => false,
+    };
+
+    if due {
+        if let Some((pending, _)) = save.pending.take() {
+            pending.save();
+            save.saved = Some(pending);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod storage {
+    use super::SETTINGS_NAME;
+    use bevy::prelude::*;
+    use std::{fs, path::PathBuf};
+
+    fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("uno").join(SETTINGS_NAME))
+    }
+
+    pub fn read() -> Option<String> {
+        fs::read_to_string(path()?).ok()
+    }
+
+    pub fn write(content: &str) {
+        let path = match path() {
+            Some(path) => path,
+            None => {
+                warn!("No config directory, the settings won't be saved");
+                return;
+            }
+        };
+
+        if let Some(dir) = path.parent() {
+            if let Err(err) = fs::create_dir_all(dir) {
+                error!("Couldn't create {}: {err}", dir.display());
+                return;
+            }
+        }
+
+        if let Err(err) = fs::write(&path, content) {
+            error!("Couldn't save the settings to {}: {err}", path.display());
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod storage {
+    use super::SETTINGS_NAME;
+    use bevy::prelude::*;
+
+    fn local_storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+
+    pub fn read() -> Option<String> {
+        local_storage()?.get_item(SETTINGS_NAME).ok()?
+    }
+
+    pub fn write(content: &str) {
+        match local_storage() {
+            Some(storage) => {
+                if storage.set_item(SETTINGS_NAME, content).is_err() {
+                    error!("Couldn't save the settings to the local storage");
+                }
+            }
+            None => warn!("No local storage, the settings won't be saved"),
+        }
+    }
+}
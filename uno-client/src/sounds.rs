@@ -0,0 +1,413 @@
+use crate::{
+    game::{CardPlayedEvent, DrawCardEvent, GameActionEvent, GameEndEvent, PlayCardEvent},
+    GameState, PlayerId, Settings,
+};
+use bevy::{
+    audio::{AudioSink, AudioSource},
+    prelude::*,
+};
+use std::{collections::HashMap, f32::consts::TAU};
+use uno::network::protocol::{GameActionKind, Player, TableState};
+
+const SAMPLE_RATE: u32 = 22050;
+const MUSIC_BEAT_S: f32 = 0.625;
+/// Bass note and notes of the arpeggio of each bar of the music: C, G, Am, F
+const MUSIC_CHORDS: [(f32, [f32; 3]); 4] = [
+    (130.8, [261.6, 329.6, 392.0]),
+    (98.0, [196.0, 246.9, 293.7]),
+    (110.0, [220.0, 261.6, 329.6]),
+    (87.3, [174.6, 220.0, 261.6]),
+];
+
+pub struct SoundsPlugin;
+
+/// Sounds of the game, synthesized at startup so that no audio file has to be shipped
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Sound {
+    Deal,
+    Play,
+    Draw,
+    Shuffle,
+    Uno,
+    CounterUno,
+    YourTurn,
+    /// Short jingle closing the game
+    GameEnd,
+}
+
+impl Sound {
+    const ALL: [Sound; 8] = [
+        Sound::Deal,
+        Sound::Play,
+        Sound::Draw,
+        Sound::Shuffle,
+        Sound::Uno,
+        Sound::CounterUno,
+        Sound::YourTurn,
+        Sound::GameEnd,
+    ];
+
+    /// Samples of the sound between -1 and 1
+    fn samples(self) -> Vec<f32> {
+        let mut noise = Noise(0x2545_f491);
+
+        match self {
+            Sound::Deal => (0..7)
+                .flat_map(|_| {
+                    let mut samples = noise.burst(0.04, 60.0);
+                    samples.extend(silence(0.05));
+                    samples
+                })
+                .collect(),
+            Sound::Play => mix(noise.burst(0.08, 40.0), tone(180.0, 0.08, 30.0), 0.6),
+            Sound::Draw => noise
+                .burst(0.15, 20.0)
+                .into_iter()
+                .map(|sample| sample * 0.6)
+                .collect(),
+            Sound::Shuffle => (0..16)
+                .flat_map(|_| {
+                    let mut samples = noise.burst(0.025, 80.0);
+                    samples.extend(silence(0.015));
+                    samples
+                })
+                .collect(),
+            Sound::Uno => notes(&[(659.3, 0.12), (880.0, 0.25)]),
+            Sound::CounterUno => notes(&[(880.0, 0.12), (523.3, 0.25)]),
+            Sound::YourTurn => notes(&[(1046.5, 0.15), (1046.5, 0.3)]),
+            Sound::GameEnd => notes(&[(523.3, 0.15), (659.3, 0.15), (784.0, 0.15), (1046.5, 0.5)]),
+        }
+    }
+}
+
+/// Pseudo-random generator for the noise of the card sounds, seeded so that a sound is always
+/// the same
+struct Noise(u32);
+
+impl Noise {
+    fn next(&mut self) -> f32 {
+        // xorshift32
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+
+        self.0 as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+
+    /// Noise fading out at the speed of `decay`, like a card hitting the table
+    fn burst(&mut self, duration: f32, decay: f32) -> Vec<f32> {
+        (0..sample_count(duration))
+            .map(|index| self.next() * (-decay * index as f32 / SAMPLE_RATE as f32).exp())
+            .collect()
+    }
+}
+
+fn sample_count(duration: f32) -> usize {
+    (duration * SAMPLE_RATE as f32) as usize
+}
+
+fn silence(duration: f32) -> Vec<f32> {
+    vec![0.0; sample_count(duration)]
+}
+
+fn tone(frequency: f32, duration: f32, decay: f32) -> Vec<f32> {
+    (0..sample_count(duration))
+        .map(|index| {
+            let time = index as f32 / SAMPLE_RATE as f32;
+            (TAU * frequency * time).sin() * (-decay * time).exp()
+        })
+        .collect()
+}
+
+/// Bell-like notes played one after the other
+fn notes(notes: &[(f32, f32)]) -> Vec<f32> {
+    notes
+        .iter()
+        .flat_map(|&(frequency, duration)| {
+            let fundamental = tone(frequency, duration, 8.0);
+            let harmonic = tone(frequency * 2.0, duration, 12.0);
+            mix(fundamental, harmonic, 0.3)
+        })
+        .map(|sample| sample * 0.5)
+        .collect()
+}
+
+/// Calm loop played under the sounds, a bass note and an arpeggio for each chord
+fn music_samples() -> Vec<f32> {
+    let bar = MUSIC_BEAT_S * 4.0;
+    let mut samples = silence(music_duration());
+
+    for (index, (bass, arpeggio)) in MUSIC_CHORDS.iter().enumerate() {
+        let start = index as f32 * bar;
+        add_at(&mut samples, start, &tone(*bass, bar, 1.2), 0.3);
+
+        for (step, note) in [0, 1, 2, 1, 0, 1, 2, 1].into_iter().enumerate() {
+            let eighth = MUSIC_BEAT_S / 2.0;
+            let note = tone(arpeggio[note], eighth, 10.0);
+            add_at(&mut samples, start + step as f32 * eighth, &note, 0.2);
+        }
+    }
+
+    samples
+}
+
+fn music_duration() -> f32 {
+    MUSIC_BEAT_S * 4.0 * MUSIC_CHORDS.len() as f32
+}
+
+/// Add `other` to `samples` from the time `start`, at the given level
+fn add_at(samples: &mut [f32], start: f32, other: &[f32], level: f32) {
+    let offset = sample_count(start);
+    for (sample, other) in samples.iter_mut().skip(offset).zip(other) {
+        *sample = (*sample + other * level).clamp(-1.0, 1.0);
+    }
+}
+
+/// Add `other` to `samples` at the given level, the result is as long as the longest one
+fn mix(mut samples: Vec<f32>, other: Vec<f32>, level: f32) -> Vec<f32> {
+    if samples.len() < other.len() {
+        samples.resize(other.len(), 0.0);
+    }
+    for (sample, other) in samples.iter_mut().zip(other) {
+        *sample = (*sample + other * level).clamp(-1.0, 1.0);
+    }
+
+    samples
+}
+
+/// 16 bits mono WAV file of the samples. The sounds are played without keeping their sink, so
+/// their volume is applied to the samples
+fn wav(samples: &[f32], volume: f32) -> Vec<u8> {
+    let data_size = samples.len() as u32 * 2;
+    let mut bytes = Vec::with_capacity(44 + data_size as usize);
+
+    bytes.extend(b"RIFF");
+    bytes.extend((36 + data_size).to_le_bytes());
+    bytes.extend(b"WAVEfmt ");
+    bytes.extend(16u32.to_le_bytes());
+    // PCM, mono
+    bytes.extend(1u16.to_le_bytes());
+    bytes.extend(1u16.to_le_bytes());
+    bytes.extend(SAMPLE_RATE.to_le_bytes());
+    bytes.extend((SAMPLE_RATE * 2).to_le_bytes());
+    // Block align and bits per sample
+    bytes.extend(2u16.to_le_bytes());
+    bytes.extend(16u16.to_le_bytes());
+    bytes.extend(b"data");
+    bytes.extend(data_size.to_le_bytes());
+
+    for sample in samples {
+        let sample = (sample * volume).clamp(-1.0, 1.0) * i16::MAX as f32;
+        bytes.extend((sample as i16).to_le_bytes());
+    }
+
+    bytes
+}
+
+// Ressources
+/// Sounds at the volume of the settings
+#[derive(Default)]
+struct Sounds {
+    sources: HashMap<Sound, Handle<AudioSource>>,
+    volume: Option<f32>,
+}
+
+/// Background music, started again each time the loop ends
+struct Music {
+    source: Option<Handle<AudioSource>>,
+    sink: Option<Handle<AudioSink>>,
+    /// Time until the end of the loop
+    timer: Timer,
+}
+
+impl Default for Music {
+    fn default() -> Self {
+        Music {
+            source: None,
+            sink: None,
+            timer: Timer::from_seconds(music_duration(), false),
+        }
+    }
+}
+
+// Events
+pub struct PlaySoundEvent(pub Sound);
+
+impl Plugin for SoundsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Sounds>()
+            .init_resource::<Music>()
+            .add_event::<PlaySoundEvent>()
+            .add_system(update_sounds)
+            .add_system(play_music)
+            .add_system_set(SystemSet::on_enter(GameState::Game).with_system(deal_sound))
+            .add_system(card_sounds)
+            .add_system(call_sounds)
+            .add_system(turn_sound)
+            .add_system(shuffle_sound)
+            .add_system(game_end_sound)
+            .add_system_to_stage(CoreStage::PostUpdate, play_sounds);
+    }
+}
+
+/// Synthesize the sounds again when the volume changes
+fn update_sounds(
+    settings: Res<Settings>,
+    mut sounds: ResMut<Sounds>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    if sounds.volume == Some(settings.volume) {
+        return;
+    }
+
+    sounds.volume = Some(settings.volume);
+    sounds.sources = Sound::ALL
+        .into_iter()
+        .map(|sound| {
+            let source = AudioSource {
+                bytes: wav(&sound.samples(), settings.volume).into(),
+            };
+
+            (sound, audio_sources.add(source))
+        })
+        .collect();
+}
+
+/// Keep the music playing at its volume, it is paused while muted
+fn play_music(
+    time: Res<Time>,
+    audio: Res<Audio>,
+    audio_sinks: Res<Assets<AudioSink>>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+    settings: Res<Settings>,
+    mut music: ResMut<Music>,
+) {
+    let music = &mut *music;
+    let silent = settings.mute || settings.music_volume <= 0.0;
+
+    // The sink only exists once the audio output has started the music
+    if let Some(sink) = music.sink.as_ref().and_then(|sink| audio_sinks.get(sink)) {
+        sink.set_volume(settings.music_volume);
+
+        if silent {
+            sink.pause();
+        } else {
+            sink.play();
+        }
+    }
+
+    if silent {
+        return;
+    }
+
+    if music.sink.is_none() || music.timer.tick(time.delta()).just_finished() {
+        let source = music
+            .source
+            .get_or_insert_with(|| {
+                audio_sources.add(AudioSource {
+                    bytes: wav(&music_samples(), 1.0).into(),
+                })
+            })
+            .clone();
+
+        music.sink = Some(audio_sinks.get_handle(audio.play(source)));
+        music.timer.reset();
+    }
+}
+
+fn play_sounds(
+    mut play_sound_events: EventReader<PlaySoundEvent>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    settings: Res<Settings>,
+) {
+    for PlaySoundEvent(sound) in play_sound_events.iter() {
+        if settings.mute || settings.volume <= 0.0 {
+            continue;
+        }
+
+        if let Some(source) = sounds.sources.get(sound) {
+            audio.play(source.clone());
+        }
+    }
+}
+
+fn deal_sound(mut play_sound_event: EventWriter<PlaySoundEvent>) {
+    play_sound_event.send(PlaySoundEvent(Sound::Deal));
+}
+
+/// Cards played by the player, by the other players, and drawn
+fn card_sounds(
+    mut play_sound_event: EventWriter<PlaySoundEvent>,
+    mut play_card_events: EventReader<PlayCardEvent>,
+    mut card_played_events: EventReader<CardPlayedEvent>,
+    mut draw_card_events: EventReader<DrawCardEvent>,
+) {
+    for _ in play_card_events.iter().chain(card_played_events.iter()) {
+        play_sound_event.send(PlaySoundEvent(Sound::Play));
+    }
+
+    for _ in draw_card_events.iter() {
+        play_sound_event.send(PlaySoundEvent(Sound::Draw));
+    }
+}
+
+fn call_sounds(
+    mut play_sound_event: EventWriter<PlaySoundEvent>,
+    mut game_action_events: EventReader<GameActionEvent>,
+) {
+    for GameActionEvent(action) in game_action_events.iter() {
+        match *action.kind {
+            GameActionKind::Uno => play_sound_event.send(PlaySoundEvent(Sound::Uno)),
+            GameActionKind::CounterUno => play_sound_event.send(PlaySoundEvent(Sound::CounterUno)),
+            _ => {}
+        }
+    }
+}
+
+/// Alert the player when their turn starts
+fn turn_sound(
+    mut play_sound_event: EventWriter<PlaySoundEvent>,
+    players_query: Query<&Player>,
+    player_id: Res<PlayerId>,
+    game_state: Res<State<GameState>>,
+    mut was_playing: Local<bool>,
+) {
+    let playing = game_state.current() == &GameState::Game
+        && players_query
+            .iter()
+            .any(|player| Some(*player.id) == **player_id && *player.is_playing);
+
+    if playing && !*was_playing {
+        play_sound_event.send(PlaySoundEvent(Sound::YourTurn));
+    }
+    *was_playing = playing;
+}
+
+/// The draw pile only grows when the discard is shuffled into it
+fn shuffle_sound(
+    mut play_sound_event: EventWriter<PlaySoundEvent>,
+    table_state_query: Query<&TableState>,
+    mut last_draw_pile_size: Local<Option<usize>>,
+) {
+    let draw_pile_size = table_state_query
+        .get_single()
+        .ok()
+        .map(|table_state| *table_state.draw_pile_size);
+
+    if let (Some(size), Some(last_size)) = (draw_pile_size, *last_draw_pile_size) {
+        if size > last_size {
+            play_sound_event.send(PlaySoundEvent(Sound::Shuffle));
+        }
+    }
+    *last_draw_pile_size = draw_pile_size;
+}
+
+fn game_end_sound(
+    mut play_sound_event: EventWriter<PlaySoundEvent>,
+    mut game_end_events: EventReader<GameEndEvent>,
+) {
+    for GameEndEvent in game_end_events.iter() {
+        play_sound_event.send(PlaySoundEvent(Sound::GameEnd));
+    }
+}
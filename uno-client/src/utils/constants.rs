@@ -11,15 +11,40 @@ pub const Z_INCREASE: f32 = 0.01;
 pub const DISCARD_Z_INCREASE: f32 = 0.001;
 
 pub const DECK_POS: (f32, f32) = (-CARD_WIDTH / 2.0 - 20.0, 50.0);
+pub const MAX_DECK_LAYERS: usize = 8;
+pub const DECK_LAYER_OFFSET: f32 = 1.5;
 pub const DISCARD_POS: (f32, f32) = (CARD_WIDTH / 2.0 + 20.0, 50.0);
 
 pub const CARD_DROP_ZONE: f32 = CARD_WIDTH;
-
-use bevy_egui::egui;
-use uno::card::Color;
-pub const COLORS: [(Color, egui::Color32); 4] = [
-    (Color::Yellow, egui::Color32::from_rgb(255, 255, 22)),
-    (Color::Red, egui::Color32::from_rgb(237, 28, 36)),
-    (Color::Blue, egui::Color32::from_rgb(0, 114, 188)),
-    (Color::Green, egui::Color32::from_rgb(80, 170, 68)),
-];
+/// Height by which the card selected with the keyboard or a gamepad sticks out of the hand
+pub const SELECTED_CARD_RAISE: f32 = 40.0;
+/// Height by which the cards that can be played stick out of the hand during the player's turn
+pub const PLAYABLE_CARD_RAISE: f32 = 20.0;
+/// Height by which the card under the cursor sticks out of the hand
+pub const HOVERED_CARD_RAISE: f32 = 30.0;
+/// Part of the window height the cards of the hand can take at most
+pub const HAND_HEIGHT_RATIO: f32 = 0.3;
+/// Part of a card still visible when the hand is compressed the most
+pub const MIN_VISIBLE_CARD_PART: f32 = 0.3;
+/// Larger hands are scrolled
+pub const MAX_VISIBLE_CARDS: usize = 20;
+/// Angle in radians between the first and the last card of the fanned hand
+pub const FAN_ANGLE: f32 = 0.5;
+pub const FAN_MAX_ANGLE_STEP: f32 = 0.08;
+/// Radius of the arc of the hand, the side cards go lower than the middle ones
+pub const FAN_RADIUS: f32 = 1500.0;
+pub const DIMMED_CARD_COLOR: bevy::prelude::Color = bevy::prelude::Color::rgb(0.55, 0.55, 0.55);
+/// Opponents' hands are drawn under the cards of the table
+pub const SEAT_Z: f32 = 0.5;
+pub const SEAT_CARD_SCALE: f32 = 0.35;
+pub const SEAT_CARD_SPACING: f32 = 12.0;
+/// Angle in radians between two cards of an opponent's hand
+pub const SEAT_CARD_ANGLE: f32 = 0.06;
+/// Larger hands of opponents are shown with this number of cards, their size is in the label
+pub const MAX_SEAT_CARDS: usize = 15;
+/// Size of the remaining cards revealed on the end game screen
+pub const END_CARD_SCALE: f32 = 0.15;
+/// Older entries of the action feed are forgotten
+pub const MAX_FEED_ENTRIES: usize = 50;
+/// Time the reason of a penalty stays next to the name of the opponent who drew
+pub const DRAW_REASON_TIME_S: f32 = 2.0;
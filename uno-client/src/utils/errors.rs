@@ -1,5 +1,7 @@
+use crate::Settings;
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContext};
+use uno::texts::{TextId, Texts};
 
 #[derive(Component)]
 pub struct Error {
@@ -10,10 +12,14 @@ pub fn display_error(
     mut commands: Commands,
     mut egui_context: ResMut<EguiContext>,
     mut query: Query<(Entity, &mut Error)>,
+    settings: Res<Settings>,
+    texts: Res<Texts>,
 ) {
+    let language = &settings.language;
+
     for (entity, error) in query.iter_mut() {
         egui::Window::new(
-            egui::RichText::new("Error")
+            egui::RichText::new(texts.get(TextId::Error, language))
                 .strong()
                 .color(egui::Color32::RED),
         )
@@ -24,7 +30,7 @@ pub fn display_error(
             ui.vertical_centered(|ui| {
                 ui.heading(&error.message);
                 ui.add_space(10.0);
-                if ui.button("Ok").clicked() {
+                if ui.button(texts.get(TextId::Ok, language)).clicked() {
                     commands.entity(entity).despawn();
                 }
             });
@@ -0,0 +1,90 @@
+use crate::{game::Games, lobbies::InLobby, server::UserKeyComponent, Global};
+use bevy_ecs::prelude::*;
+use bevy_log::warn;
+use naia_bevy_server::{Server, UserKey};
+use uno::{
+    chat::{sanitize_chat_message, ChatScope},
+    network::{
+        protocol::{ChatMessage, Player as NetworkPlayer},
+        Channels, Protocol,
+    },
+    Player,
+};
+
+// Events
+pub struct ChatEvent {
+    pub user_key: UserKey,
+    pub text: String,
+}
+
+/// Forward a chat message to everyone in the same lobby, or in the same game once it started.
+/// Users that are in neither can't chat
+pub fn chat(
+    mut server: Server<Protocol, Channels>,
+    mut chat_events: EventReader<ChatEvent>,
+    global: Res<Global>,
+    games: Res<Games>,
+    lobby_players_query: Query<(&UserKeyComponent, &Player, &InLobby)>,
+    network_players_query: Query<&NetworkPlayer>,
+) {
+    for ChatEvent { user_key, text } in chat_events.iter() {
+        let text = match sanitize_chat_message(text) {
+            Some(text) => text,
+            None => continue,
+        };
+
+        let sender_id = match global
+            .user_keys_entities
+            .get(user_key)
+            .and_then(|entity| network_players_query.get(*entity).ok())
+        {
+            Some(network_player) => *network_player.id,
+            None => continue,
+        };
+
+        let lobby_id = global
+            .lobbies_room_key
+            .iter()
+            .find(|(_, room_key)| server.room(room_key).has_user(user_key))
+            .map(|(lobby_id, _)| *lobby_id);
+
+        let lobby_id = match lobby_id {
+            Some(lobby_id) => lobby_id,
+            None => {
+                warn!("Received a chat message from a user that isn't in a lobby");
+                continue;
+            }
+        };
+
+        let (scope, recipients): (_, Vec<(UserKey, String)>) = match games.get(&lobby_id) {
+            Some(game) => (
+                ChatScope::Game,
+                game.players
+                    .iter()
+                    .zip(&game.rules.seats)
+                    .map(|(player, seat)| (player.user_key, seat.player.username.clone()))
+                    .collect(),
+            ),
+            None => (
+                ChatScope::Lobby,
+                lobby_players_query
+                    .iter()
+                    .filter(|(_, _, in_lobby)| ***in_lobby == lobby_id)
+                    .map(|(player_user_key, player, _)| {
+                        (**player_user_key, player.username.clone())
+                    })
+                    .collect(),
+            ),
+        };
+
+        let username = match recipients.iter().find(|(key, _)| key == user_key) {
+            Some((_, username)) => username.clone(),
+            None => continue,
+        };
+
+        let message = ChatMessage::new(sender_id, username, text, scope);
+        for (recipient, _) in &recipients {
+            server.send_message(recipient, Channels::Chat, &message);
+        }
+    }
+}
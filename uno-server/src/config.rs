@@ -0,0 +1,123 @@
+use bevy_log::error;
+use std::{env, fs, net::SocketAddr, time::Duration};
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:3478";
+const DEFAULT_BAN_DURATION_S: u64 = 10 * 60;
+const DEFAULT_TURN_TIMEOUT_S: u64 = 60;
+
+/// Settings of the server, read from the environment or from a `.env` file
+pub struct Config {
+    /// Address on which the clients start their session
+    pub session_address: SocketAddr,
+    /// Address on which the WebRTC data channel listens
+    pub webrtc_address: SocketAddr,
+    /// URL the clients use to reach the WebRTC data channel
+    pub public_webrtc_url: String,
+    /// Words that can't appear in a username
+    pub username_blocklist: Vec<String>,
+    /// Address of the HTTP endpoint serving the metrics, none if they aren't served
+    pub metrics_address: Option<SocketAddr>,
+    /// How long the address of a user flooding the server is refused. Such users are only
+    /// disconnected if it is zero
+    pub ban_duration: Duration,
+    /// How long a player has to play before drawing a card and losing their turn, none if the
+    /// turns aren't timed
+    pub turn_timeout: Option<Duration>,
+}
+
+impl Config {
+    /// Read the configuration from the environment:
+    /// * `UNO_SESSION_ADDR`: address the sessions listen on, `127.0.0.1:3478` by default
+    /// * `UNO_WEBRTC_ADDR`: address the data channel listens on, the session address by default
+    /// * `UNO_PUBLIC_WEBRTC_URL`: URL of the data channel as seen by the clients
+    /// * `UNO_USERNAME_BLOCKLIST`: path to a file containing one blocked word per line
+    /// * `UNO_METRICS_ADDR`: address serving the metrics on `/metrics`, like `127.0.0.1:9100`.
+    ///   The metrics aren't served if it isn't set
+    /// * `UNO_BAN_DURATION_S`: seconds during which a flooding address is banned, 10 minutes by
+    ///   default
+    /// * `UNO_TURN_TIMEOUT_S`: seconds a player has to play their turn, 1 minute by default. The
+    ///   turns aren't timed if it is zero
+    pub fn from_env() -> Config {
+        let session_address = socket_address_from_env("UNO_SESSION_ADDR", DEFAULT_ADDRESS);
+        let webrtc_address =
+            socket_address_from_env("UNO_WEBRTC_ADDR", &session_address.to_string());
+        let public_webrtc_url = env::var("UNO_PUBLIC_WEBRTC_URL")
+            .unwrap_or_else(|_| format!("http://{webrtc_address}"));
+
+        let username_blocklist = match env::var("UNO_USERNAME_BLOCKLIST") {
+            Ok(path) => match fs::read_to_string(&path) {
+                Ok(content) => content
+                    .lines()
+                    .map(|line| line.trim().to_owned())
+                    .filter(|word| !word.is_empty())
+                    .collect(),
+                Err(err) => {
+                    error!("Couldn't read the username blocklist {path}: {err}");
+                    vec![]
+                }
+            },
+            Err(_) => vec![],
+        };
+
+        let metrics_address =
+            env::var("UNO_METRICS_ADDR")
+                .ok()
+                .and_then(|address| match address.parse() {
+                    Ok(address) => Some(address),
+                    Err(err) => {
+                        error!("Invalid address {address} in UNO_METRICS_ADDR: {err}");
+                        None
+                    }
+                });
+
+        let ban_duration = match env::var("UNO_BAN_DURATION_S") {
+            Ok(seconds) => seconds.parse().unwrap_or_else(|err| {
+                error!("Invalid duration {seconds} in UNO_BAN_DURATION_S: {err}");
+                DEFAULT_BAN_DURATION_S
+            }),
+            Err(_) => DEFAULT_BAN_DURATION_S,
+        };
+
+        let turn_timeout = match env::var("UNO_TURN_TIMEOUT_S") {
+            Ok(seconds) => seconds.parse().unwrap_or_else(|err| {
+                error!("Invalid duration {seconds} in UNO_TURN_TIMEOUT_S: {err}");
+                DEFAULT_TURN_TIMEOUT_S
+            }),
+            Err(_) => DEFAULT_TURN_TIMEOUT_S,
+        };
+
+        Config {
+            session_address,
+            webrtc_address,
+            public_webrtc_url,
+            username_blocklist,
+            metrics_address,
+            ban_duration: Duration::from_secs(ban_duration),
+            turn_timeout: (turn_timeout > 0).then(|| Duration::from_secs(turn_timeout)),
+        }
+    }
+
+    /// Configuration of a server only reachable from this machine, on the given port
+    pub fn local(port: u16) -> Config {
+        let address = SocketAddr::from(([127, 0, 0, 1], port));
+
+        Config {
+            session_address: address,
+            webrtc_address: address,
+            public_webrtc_url: format!("http://{address}"),
+            username_blocklist: vec![],
+            metrics_address: None,
+            ban_duration: Duration::from_secs(DEFAULT_BAN_DURATION_S),
+            turn_timeout: Some(Duration::from_secs(DEFAULT_TURN_TIMEOUT_S)),
+        }
+    }
+}
+
+fn socket_address_from_env(key: &str, default: &str) -> SocketAddr {
+    let address = env::var(key).unwrap_or_else(|_| default.to_owned());
+
+    address.parse().unwrap_or_else(|err| {
+        error!("Invalid address {address} in {key}: {err}, using {default}");
+        default.parse().unwrap()
+    })
+}
@@ -1,21 +1,28 @@
 use crate::{
-    game::{
-        CardPlayedEvent, ColorChosenEvent, CounterUnoEvent, DrawCardEvent, GameExitEvent,
-        StartGameEvent, UnoEvent,
-    },
+    chat::ChatEvent,
+    config::Config,
+    game::{GameActionEvent, GameExitEvent, PlayAgainEvent, StartGameEvent},
     lobbies::{CreateLobbyEvent, JoinLobbyEvent, LeaveLobbyEvent},
+    metrics::Metrics,
+    rate_limit::{MessageKind, RateLimiter, Verdict},
+    scope::PrivateTo,
     server::{UserKeyComponent, UsernameChangedEvent},
     Global,
 };
-use bevy_ecs::prelude::*;
-use bevy_log::{error, info};
+use bevy_core::Time;
+use bevy_ecs::{prelude::*, system::SystemParam};
+use bevy_log::{error, info, warn};
 use naia_bevy_server::{
     events::{AuthorizationEvent, ConnectionEvent, DisconnectionEvent, MessageEvent},
     Server,
 };
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::{
+    collections::HashSet,
+    sync::atomic::{AtomicU64, Ordering},
+};
 use uno::{
     network::{
+        check_protocol_version,
         protocol::{Player as NetworkPlayer, *},
         Channels, Protocol,
     },
@@ -30,8 +37,37 @@ fn new_player_id() -> u64 {
 pub fn authorization_event(
     mut auth_events: EventReader<AuthorizationEvent<Protocol>>,
     mut server: Server<Protocol, Channels>,
+    mut global: ResMut<Global>,
+    rate_limiter: Res<RateLimiter>,
+    time: Res<Time>,
 ) {
-    for AuthorizationEvent(user_key, _) in auth_events.iter() {
+    for AuthorizationEvent(user_key, auth) in auth_events.iter() {
+        let address = server.user(user_key).address();
+        if rate_limiter.is_banned(&address.ip(), time.seconds_since_startup()) {
+            warn!("Rejecting banned address {address}");
+            server.reject_connection(user_key);
+            continue;
+        }
+
+        let handshake = match auth {
+            Protocol::Handshake(handshake) => handshake,
+            _ => {
+                warn!("Rejecting a client that didn't send a handshake");
+                server.reject_connection(user_key);
+                continue;
+            }
+        };
+
+        // Incompatible clients are accepted only to be sent the version of the server, a
+        // rejection can't carry any message
+        if let Err(err) = check_protocol_version(*handshake.protocol_version) {
+            warn!(
+                "Refusing client version {}: {err}",
+                *handshake.client_version
+            );
+            global.incompatible_users.insert(*user_key);
+        }
+
         server.accept_connection(user_key);
     }
 }
@@ -43,29 +79,39 @@ pub fn connection_event(
     mut server: Server<Protocol, Channels>,
 ) {
     for ConnectionEvent(user_key) in connection_events.iter() {
+        if global.incompatible_users.contains(user_key) {
+            server.send_message(
+                user_key,
+                Channels::Uno,
+                &IncompatibleVersion::new(env!("CARGO_PKG_VERSION").to_owned()),
+            );
+            global.disconnect_after_send.insert(*user_key);
+            continue;
+        }
+
         info!("New connection");
 
         commands
             .spawn()
-            .insert(Player::default())
+            .insert(Player::new(String::new()))
             .insert(UserKeyComponent(*user_key));
 
         server.user_mut(user_key).enter_room(&global.main_room_key);
 
         let player_id = new_player_id();
-        server.send_message(user_key, Channels::Uno, &YourPlayerId::new(player_id));
 
-        server.spawn().enter_room(&global.main_room_key);
+        let session = server
+            .spawn()
+            .enter_room(&global.main_room_key)
+            .insert(YourPlayerId::new(player_id))
+            .id();
+        commands.entity(session).insert(PrivateTo(*user_key));
+        global.session_entities.insert(*user_key, session);
 
         let id = server
             .spawn()
             .enter_room(&global.main_room_key)
-            .insert(NetworkPlayer::new(
-                player_id,
-                None,
-                "Unknown Player".to_owned(),
-                0,
-            ))
+            .insert(NetworkPlayer::new(player_id, None, String::new(), 0))
             .id();
 
         global.user_keys_entities.insert(*user_key, id);
@@ -76,16 +122,27 @@ pub fn disconnection_event(
     mut global: ResMut<Global>,
     mut server: Server<Protocol, Channels>,
     mut disconnection_events: EventReader<DisconnectionEvent>,
+    mut rate_limiter: ResMut<RateLimiter>,
     // this_player_query: Query<(Entity, &ThisPlayer)>,
     players_query: Query<(Entity, &UserKeyComponent), With<Player>>,
 ) {
     for DisconnectionEvent(user_key, _) in disconnection_events.iter() {
+        if global.incompatible_users.remove(user_key) {
+            continue;
+        }
+
         info!("A user disconnected");
 
+        rate_limiter.forget(user_key);
+
         server
             .entity_mut(&global.user_keys_entities[user_key])
             .despawn();
 
+        if let Some(session) = global.session_entities.remove(user_key) {
+            server.entity_mut(&session).despawn();
+        }
+
         /*
         for (entity, this_player) in this_player_query.iter() {
             if global.user_keys_entities[user_key] == Entity::from_bits(*this_player.entity) {
@@ -108,25 +165,66 @@ pub fn disconnection_event(
     }
 }
 
+/// Writers of the events of the game actions, grouped to keep `message_event` under the limit
+/// of system parameters
+#[derive(SystemParam)]
+pub struct GameActionWriters<'w, 's> {
+    game_action: EventWriter<'w, 's, GameActionEvent>,
+    game_exit: EventWriter<'w, 's, GameExitEvent>,
+    play_again: EventWriter<'w, 's, PlayAgainEvent>,
+}
+
 pub fn message_event(
     global: Res<Global>,
-    server: Server<Protocol, Channels>,
+    config: Res<Config>,
+    time: Res<Time>,
+    mut rate_limiter: ResMut<RateLimiter>,
+    mut metrics: ResMut<Metrics>,
+    mut server: Server<Protocol, Channels>,
     mut message_events: EventReader<MessageEvent<Protocol, Channels>>,
     mut create_lobby_event: EventWriter<CreateLobbyEvent>,
     mut join_lobby_event: EventWriter<JoinLobbyEvent>,
     mut leave_lobby_event: EventWriter<LeaveLobbyEvent>,
     mut username_change_event: EventWriter<UsernameChangedEvent>,
     mut start_game_event: EventWriter<StartGameEvent>,
-    mut card_validation_event: EventWriter<CardPlayedEvent>,
-    mut color_chosen_event: EventWriter<ColorChosenEvent>,
-    mut draw_card_event: EventWriter<DrawCardEvent>,
-    mut uno_event: EventWriter<UnoEvent>,
-    mut counter_uno_event: EventWriter<CounterUnoEvent>,
-    mut game_exit_event: EventWriter<GameExitEvent>,
+    mut game_action_writers: GameActionWriters,
+    mut chat_event: EventWriter<ChatEvent>,
 ) {
+    let mut banned_users = HashSet::new();
+
     for MessageEvent(user_key, _channel, protocol) in message_events.iter() {
         info!("received message");
 
+        if banned_users.contains(user_key) || global.incompatible_users.contains(user_key) {
+            continue;
+        }
+
+        let kind = MessageKind::of(protocol);
+        *metrics.messages.entry(kind).or_insert(0) += 1;
+
+        match rate_limiter.check(*user_key, kind, time.seconds_since_startup()) {
+            Verdict::Allow => {}
+            Verdict::Drop => {
+                warn!("Dropping {kind:?} message, the user is sending too many of them");
+                continue;
+            }
+            Verdict::Ban => {
+                let address = server.user(user_key).address();
+                warn!(
+                    "Banning {address} for {}s for flooding the server",
+                    config.ban_duration.as_secs()
+                );
+                rate_limiter.ban(
+                    address.ip(),
+                    config.ban_duration,
+                    time.seconds_since_startup(),
+                );
+                banned_users.insert(*user_key);
+                server.user_mut(user_key).disconnect();
+                continue;
+            }
+        }
+
         let mut user_lobby = None;
         for (lobby_id, room_key) in &global.lobbies_room_key {
             if server.room(room_key).has_user(user_key) {
@@ -148,38 +246,40 @@ pub fn message_event(
                 username: (*player.username).to_owned(),
                 user_key: *user_key,
             }),
-            Protocol::StartGame(_) => start_game_event.send(StartGameEvent {
-                lobby_id: user_lobby.unwrap(),
-            }),
-            Protocol::CardPlayed(CardPlayed { color, value }) => {
-                card_validation_event.send(CardPlayedEvent {
-                    user_key: *user_key,
-                    game_id: user_lobby.unwrap(),
-                    card: (**color, **value).into(),
-                })
-            }
-            Protocol::ColorChosen(ColorChosen { color }) => {
-                color_chosen_event.send(ColorChosenEvent {
-                    color: (**color).into(),
-                    game_id: user_lobby.unwrap(),
-                })
+            Protocol::StartGame(_) => match user_lobby {
+                Some(lobby_id) => start_game_event.send(StartGameEvent { lobby_id }),
+                None => error!("Received a start game request from a user that isn't in a lobby"),
+            },
+            Protocol::GameAction(action) => {
+                let game_id = match user_lobby {
+                    Some(game_id) => game_id,
+                    None => {
+                        error!("Received a game action from a user that isn't in a game");
+                        metrics.rejected_actions += 1;
+                        continue;
+                    }
+                };
+
+                let writers = &mut game_action_writers;
+                match *action.kind {
+                    GameActionKind::ExitGame => writers.game_exit.send(GameExitEvent {
+                        user_key: *user_key,
+                        game_id,
+                    }),
+                    GameActionKind::PlayAgain => writers.play_again.send(PlayAgainEvent {
+                        user_key: *user_key,
+                        game_id,
+                    }),
+                    _ => writers.game_action.send(GameActionEvent {
+                        user_key: *user_key,
+                        game_id,
+                        action: action.clone(),
+                    }),
+                }
             }
-            Protocol::Uno(_) => uno_event.send(UnoEvent {
-                user_key: *user_key,
-                game_id: user_lobby.unwrap(),
-            }),
-            Protocol::CounterUno(_) => counter_uno_event.send(CounterUnoEvent {
-                user_key: *user_key,
-                game_id: user_lobby.unwrap(),
-            }),
-            Protocol::DrawCard(_) => draw_card_event.send(DrawCardEvent {
-                user_key: *user_key,
-                game_id: user_lobby.unwrap(),
-                player_action: true,
-            }),
-            Protocol::GameExit(_) => game_exit_event.send(GameExitEvent {
+            Protocol::SendChat(message) => chat_event.send(ChatEvent {
                 user_key: *user_key,
-                game_id: user_lobby.unwrap(),
+                text: (*message.text).clone(),
             }),
             _ => error!("Received unhandled message!"),
         }
@@ -1,141 +1,126 @@
-use crate::{lobbies::InLobby, server::UserKeyComponent, Global};
+use crate::{config::Config, lobbies::InLobby, metrics::Metrics, server::UserKeyComponent, Global};
 use bevy_derive::{Deref, DerefMut};
 use bevy_ecs::prelude::*;
-use bevy_log::{error, info};
+use bevy_log::error;
 use naia_bevy_server::{Server, UserKey};
-use rand::Rng;
-use std::{collections::HashMap, default::Default};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Instant,
+};
 use uno::{
-    card::{Card, Color, Value},
     lobby::LobbyId,
     network::{
         protocol::{Player as NetworkPlayer, *},
         Channels, Protocol,
     },
     player::PlayerState,
-    Deck, Player,
+    rules::GameRules,
+    Player,
 };
 
-pub struct PassTurnEvent {
-    pub skipping: bool,
-    pub game_id: LobbyId,
-}
-
 pub struct StartGameEvent {
     pub lobby_id: LobbyId,
 }
 
-pub struct DrawCardEvent {
-    pub user_key: UserKey,
-    pub game_id: LobbyId,
-    pub player_action: bool,
-}
-
-pub struct CardPlayedEvent {
-    pub user_key: UserKey,
-    pub game_id: LobbyId,
-    pub card: Card,
-}
-
-pub struct PlayCardEvent {
-    pub card: Card,
-    pub game_id: LobbyId,
-}
-
-pub struct ColorChosenEvent {
-    pub color: Color,
-    pub game_id: LobbyId,
-}
-
-pub struct UnoEvent {
+/// Action of a player in their game, checked and applied by the rules of the game
+pub struct GameActionEvent {
     pub user_key: UserKey,
     pub game_id: LobbyId,
+    pub action: GameAction,
 }
 
-pub struct CounterUnoEvent {
+pub struct GameExitEvent {
     pub user_key: UserKey,
     pub game_id: LobbyId,
 }
 
-pub struct GameEndEvent {
-    pub game_id: LobbyId,
-}
-
-pub struct GameExitEvent {
+pub struct PlayAgainEvent {
     pub user_key: UserKey,
     pub game_id: LobbyId,
 }
 
 #[derive(Clone)]
 pub struct Game {
+    pub rules: GameRules,
+    /// Users of the seats of the rules, in the same order
     pub players: Vec<PlayerData>,
-    pub current_color: Color,
-    settings: GameSettings,
-    deck: Deck,
-    discard: Deck,
-    turn_index: usize,
-    reverse_turn: bool,
+    /// Replicated entity holding the `CurrentColor` and the `TableState` of the game
+    pub table_entity: Option<Entity>,
+    /// Players who want to play again once the game is over
+    pub play_again: HashSet<UserKey>,
+    /// Turn of the rules being timed, and when it started
+    pub turn_timer: (u32, Instant),
 }
 
-impl Default for Game {
-    fn default() -> Self {
-        Self {
-            settings: GameSettings::default(),
-            players: Vec::new(),
-            deck: Deck::full(),
-            discard: Deck::empty(),
-            turn_index: 0,
-            reverse_turn: false,
-            current_color: Color::Black,
-        }
+impl Game {
+    /// Index of the seat of a user in the game
+    pub fn seat(&self, user_key: &UserKey) -> Option<usize> {
+        self.players
+            .iter()
+            .position(|player_data| player_data.user_key == *user_key)
     }
-}
 
-impl Game {
-    fn draw_card(&mut self) -> Card {
-        if self.deck.is_empty() {
-            let top_card = self.discard.draw().unwrap();
-            self.deck = self.discard.clone();
-            self.deck.shuffle();
-
-            self.discard = Deck::empty();
-            self.discard.add(top_card);
-        }
+    /// The player whose turn it is
+    pub fn current_player(&self) -> Option<&PlayerData> {
+        self.players.get(self.rules.current_seat())
+    }
 
-        self.deck.draw().unwrap()
+    /// Stop replicating the table once the game is over for everyone
+    fn despawn_table(&mut self, server: &mut Server<Protocol, Channels>) {
+        if let Some(table_entity) = self.table_entity.take() {
+            server.entity_mut(&table_entity).despawn();
+        }
     }
 
-    fn next_player_index(&self) -> usize {
-        if self.reverse_turn {
-            if self.turn_index == 0 {
-                self.players.len() - 1
-            } else {
-                self.turn_index - 1
+    /// Send the messages produced by the rules to the users of the seats they are for
+    fn deliver(&mut self, server: &mut Server<Protocol, Channels>) {
+        for (seat, message) in self.rules.messages() {
+            let user_key = &self.players[seat].user_key;
+
+            match message {
+                Protocol::CardPlayed(message) => {
+                    server.send_message(user_key, Channels::Uno, &message)
+                }
+                Protocol::CardValidation(message) => {
+                    server.send_message(user_key, Channels::Uno, &message)
+                }
+                Protocol::DrawCard(message) => {
+                    server.send_message(user_key, Channels::Uno, &message)
+                }
+                Protocol::PlayerDrew(message) => {
+                    server.send_message(user_key, Channels::Uno, &message)
+                }
+                Protocol::Uno(message) => server.send_message(user_key, Channels::Uno, &message),
+                Protocol::StopUno(message) => {
+                    server.send_message(user_key, Channels::Uno, &message)
+                }
+                Protocol::CounterUno(message) => {
+                    server.send_message(user_key, Channels::Uno, &message)
+                }
+                Protocol::HaveToDrawCard(message) => {
+                    server.send_message(user_key, Channels::Uno, &message)
+                }
+                Protocol::GameSnapshot(message) => {
+                    server.send_message(user_key, Channels::Uno, &message)
+                }
+                Protocol::GameLog(message) => {
+                    server.send_message(user_key, Channels::Uno, &message)
+                }
+                Protocol::GameEnd(message) => {
+                    server.send_message(user_key, Channels::Uno, &message)
+                }
+                _ => error!("The rules of the game produced a message that isn't part of a game"),
             }
-        } else {
-            (self.turn_index + 1) % self.players.len()
         }
     }
 }
 
 #[derive(Clone)]
 pub struct PlayerData {
-    pub player: Player,
     pub user_key: UserKey,
     pub server_entity: Entity,
 }
 
-#[derive(Debug, Clone)]
-pub struct GameSettings {
-    initial_cards: u32,
-}
-
-impl Default for GameSettings {
-    fn default() -> Self {
-        Self { initial_cards: 7 }
-    }
-}
-
 #[derive(Clone, Deref, DerefMut)]
 pub struct Games(pub HashMap<LobbyId, Game>);
 
@@ -144,14 +129,27 @@ pub fn setup_game(
     mut server: Server<Protocol, Channels>,
     mut start_game_event: EventReader<StartGameEvent>,
     mut games: ResMut<Games>,
-    mut draw_card_event: EventWriter<DrawCardEvent>,
-    mut pass_turn_event: EventWriter<PassTurnEvent>,
     global: Res<Global>,
     lobbies_query: Query<(Entity, &Lobby)>,
     players_query: Query<(&Player, Entity, &InLobby, &UserKeyComponent)>,
-    network_players_query: Query<(Entity, &NetworkPlayer)>,
+    network_players_query: Query<&NetworkPlayer>,
 ) {
     for StartGameEvent { lobby_id } in start_game_event.iter() {
+        // Several players can ask to start the same game at once
+        if games.contains_key(lobby_id) {
+            continue;
+        }
+
+        let mut lobby_players = players_query
+            .iter()
+            .filter(|(_, _, InLobby(player_lobby_id), _)| player_lobby_id == lobby_id)
+            .collect::<Vec<_>>();
+
+        if lobby_players.is_empty() {
+            error!("Tried to start a game without players");
+            continue;
+        }
+
         // Remove the lobby
         for (entity, lobby) in lobbies_query.iter() {
             if *lobby.id == *lobby_id {
@@ -159,645 +157,227 @@ pub fn setup_game(
             }
         }
 
-        // Create the new game and shuffle the deck
-        let mut game = Game::default();
-        game.deck.shuffle();
-
-        // Draw the first card
-        let mut first_card = game.draw_card();
-        while first_card.color == Color::Black
-            || first_card.value == Value::Skip
-            || first_card.value == Value::Reverse
-            || first_card.value == Value::DrawTwo
-        {
-            game.discard.add(first_card);
-            first_card = game.draw_card();
-        }
-        game.discard.add(first_card);
-        game.current_color = first_card.color;
-        server
-            .spawn()
-            .enter_room(&global.lobbies_room_key[lobby_id])
-            .insert(CurrentColor::new(game.current_color));
-
-        for (player, entity, InLobby(player_lobby_id), user_key) in players_query.iter() {
-            if player_lobby_id == lobby_id {
-                commands.entity(entity).despawn();
-
-                server.send_message(user_key, Channels::Uno, &StartGame::new());
-
-                // Add the player to the game
-                game.players.push(PlayerData {
-                    player: player.clone(),
-                    user_key: **user_key,
-                    server_entity: global.user_keys_entities[user_key],
-                });
-
-                // Send the intial card to the player
-                for _ in 0..game.settings.initial_cards {
-                    draw_card_event.send(DrawCardEvent {
-                        user_key: **user_key,
-                        game_id: *lobby_id,
-                        player_action: false,
-                    });
-                }
+        // The players sit in the order of their entities
+        lobby_players.sort_unstable_by_key(|(_, _, _, user_key)| {
+            global.user_keys_entities[&***user_key].id()
+        });
 
-                // Send first card drawn
-                server.send_message(user_key, Channels::Uno, &CardPlayed::new(first_card));
-            }
-        }
+        let mut players = Vec::with_capacity(lobby_players.len());
+        let mut seats = Vec::with_capacity(lobby_players.len());
+        for (player, entity, _, user_key) in lobby_players {
+            commands.entity(entity).despawn();
 
-        // Randomize the first player
-        game.turn_index = rand::thread_rng().gen_range(0..game.players.len());
-        pass_turn_event.send(PassTurnEvent {
-            skipping: false,
-            game_id: *lobby_id,
-        });
+            server.send_message(user_key, Channels::Uno, &StartGame::new());
 
-        game.players
-            .sort_unstable_by(|p1, p2| p1.server_entity.id().cmp(&p2.server_entity.id()));
+            let server_entity = global.user_keys_entities[&**user_key];
+            let id = network_players_query
+                .get(server_entity)
+                .map(|network_player| *network_player.id)
+                .unwrap_or(0);
+
+            players.push(PlayerData {
+                user_key: **user_key,
+                server_entity,
+            });
+            seats.push((id, player.clone()));
+        }
+
+        let rules = GameRules::with_players(seats);
+        let table_entity = server
+            .spawn()
+            .enter_room(&global.lobbies_room_key[lobby_id])
+            .insert(CurrentColor::new(rules.current_color()))
+            .insert(TableState::default())
+            .id();
+
+        let turn_timer = (rules.turn(), Instant::now());
+        let mut game = Game {
+            rules,
+            players,
+            turn_timer,
+            table_entity: Some(table_entity),
+            play_again: HashSet::new(),
+        };
+        game.deliver(&mut server);
 
         games.insert(*lobby_id, game);
     }
 }
 
-pub fn draw_card(
+pub fn game_action(
     mut server: Server<Protocol, Channels>,
     mut games: ResMut<Games>,
-    mut draw_card_events: EventReader<DrawCardEvent>,
-    mut pass_turn_event: EventWriter<PassTurnEvent>,
+    mut game_action_events: EventReader<GameActionEvent>,
+    mut metrics: ResMut<Metrics>,
 ) {
-    for DrawCardEvent {
+    for GameActionEvent {
         user_key,
         game_id,
-        player_action,
-    } in draw_card_events.iter()
+        action,
+    } in game_action_events.iter()
     {
         let game = match games.get_mut(game_id) {
             Some(g) => g,
             None => {
-                error!("Game not found in draw_card");
+                error!("Game not found in game_action");
                 continue;
             }
         };
 
-        let card = game.draw_card();
-        for player_data in &mut game.players {
-            if player_data.user_key == *user_key {
-                player_data.player.hand.push(card);
-                server.send_message(&player_data.user_key, Channels::Uno, &DrawCard::new(card));
-
-                if *player_action {
-                    if !player_data
-                        .player
-                        .can_play(*game.discard.top().unwrap(), game.current_color)
-                    {
-                        pass_turn_event.send(PassTurnEvent {
-                            skipping: false,
-                            game_id: *game_id,
-                        });
-                    } else {
-                        player_data.player.state = PlayerState::PlayingCard;
-                    }
-                }
-
-                break;
-            }
-        }
-    }
-}
-
-pub fn pass_turn(
-    mut server: Server<Protocol, Channels>,
-    mut games: ResMut<Games>,
-    mut pass_turn_events: EventReader<PassTurnEvent>,
-) {
-    for PassTurnEvent { skipping, game_id } in pass_turn_events.iter() {
-        let game = match games.get_mut(game_id) {
-            Some(g) => g,
+        let seat = match game.seat(user_key) {
+            Some(seat) => seat,
             None => {
-                error!("Game not found in pass_turn");
+                error!("Received a game action from a user that isn't playing the game");
                 continue;
             }
         };
 
-        game.turn_index = game.next_player_index();
-
-        let current_player_user_key = game.players[game.turn_index].user_key;
-
-        for player_data in &mut game.players {
-            if player_data.user_key == current_player_user_key {
-                player_data.player.is_playing = true;
-                player_data.player.state = PlayerState::WaitingToPlay;
-            } else {
-                player_data.player.is_playing = false;
-            }
+        let was_over = game.rules.is_over();
+        if !game.rules.apply(seat, action) {
+            metrics.rejected_actions += 1;
         }
-
-        if !skipping {
-            let PlayerData {
-                player, user_key, ..
-            } = &mut game.players[game.turn_index];
-
-            if player.can_play(*game.discard.top().unwrap(), game.current_color) {
-                player.state = PlayerState::PlayingCard;
-            } else {
-                player.state = PlayerState::DrawingCard;
-                server.send_message(user_key, Channels::Uno, &HaveToDrawCard::new());
-            }
+        if !was_over && game.rules.is_over() {
+            metrics.games_finished += 1;
         }
-    }
-}
-
-pub fn card_played(
-    mut server: Server<Protocol, Channels>,
-    mut games: ResMut<Games>,
-    mut card_played_events: EventReader<CardPlayedEvent>,
-    mut play_card_event: EventWriter<PlayCardEvent>,
-) {
-    for event in card_played_events.iter() {
-        let CardPlayedEvent {
-            user_key,
-            game_id,
-            card,
-        } = event;
-
-        let game = match games.get_mut(game_id) {
-            Some(g) => g,
-            None => {
-                error!("Game not found in card_played");
-                continue;
-            }
-        };
-
-        let PlayerData { player, .. } = &mut game.players[game.turn_index];
 
-        let valid = if player.state == PlayerState::PlayingCard {
-            card.can_be_played(*game.discard.top().unwrap(), game.current_color)
-                && player.hand.contains(card)
-        } else {
-            false
-        };
-
-        server.send_message(user_key, Channels::Uno, &CardValidation::new(valid));
-
-        if valid {
-            play_card_event.send(PlayCardEvent {
-                card: *card,
-                game_id: *game_id,
-            });
-        }
+        game.deliver(&mut server);
     }
 }
 
-pub fn play_card(
+/// Make the players who take too long to play draw a card and lose their turn
+pub fn turn_timer(
     mut server: Server<Protocol, Channels>,
     mut games: ResMut<Games>,
-    mut play_card_event: EventReader<PlayCardEvent>,
-    mut pass_turn_event: EventWriter<PassTurnEvent>,
-    mut draw_card_event: EventWriter<DrawCardEvent>,
-    mut game_end_event: EventWriter<GameEndEvent>,
+    config: Res<Config>,
 ) {
-    for PlayCardEvent { card, game_id } in play_card_event.iter() {
-        let mut game = match games.get_mut(game_id) {
-            Some(g) => g,
-            None => {
-                error!("Game not found in play_card");
-                continue;
-            }
-        };
-
-        for PlayerData { user_key, .. } in &game.players {
-            if *user_key != game.players[game.turn_index].user_key {
-                server.send_message(user_key, Channels::Uno, &CardPlayed::new(*card));
-            }
-        }
-
-        let in_uno = {
-            let PlayerData { player, .. } = &mut game.players[game.turn_index];
-
-            let card_idx = if let Some(card_idx) = player.hand.iter().position(|&c| c == *card) {
-                card_idx
-            } else {
-                error!("Player has played a card they doesn't have have in their hand.");
-                return;
-            };
-
-            player.hand.remove(card_idx);
-            game.discard.add(*card);
-            game.current_color = card.color;
-
-            if player.hand.len() == 1 {
-                player.state = PlayerState::Uno;
-            }
-
-            player.state == PlayerState::Uno
-        };
-
-        if game.players[game.turn_index].player.hand.is_empty() {
-            game_end_event.send(GameEndEvent { game_id: *game_id });
-
-            return;
-        }
-
-        if in_uno {
-            let current_user_key = game.players[game.turn_index].user_key;
-
-            for player_data in &game.players {
-                if current_user_key == player_data.user_key {
-                    server.send_message(&player_data.user_key, Channels::Uno, &Uno::new());
-                } else {
-                    server.send_message(&player_data.user_key, Channels::Uno, &CounterUno::new());
-                }
-            }
-        }
-
-        let pass_turn = match card.value {
-            Value::Reverse => reverse_played(*game_id, game, in_uno, &mut pass_turn_event),
-            Value::DrawTwo => draw_two_played(
-                *game_id,
-                game,
-                in_uno,
-                &mut pass_turn_event,
-                &mut draw_card_event,
-            ),
-            Value::Skip => skip_played(*game_id, in_uno, &mut pass_turn_event),
-            Value::Wild => wild_played(game, in_uno),
-            Value::WildFour => wild_four_played(*game_id, game, in_uno, &mut draw_card_event),
-            _ => true,
-        };
-
-        if !in_uno && pass_turn {
-            pass_turn_event.send(PassTurnEvent {
-                skipping: false,
-                game_id: *game_id,
-            });
-        }
-    }
-}
-
-fn reverse_played(
-    game_id: LobbyId,
-    game: &mut Game,
-    in_uno: bool,
-    pass_turn_event: &mut EventWriter<PassTurnEvent>,
-) -> bool {
-    game.reverse_turn = !game.reverse_turn;
-
-    if game.players.len() == 2 && !in_uno {
-        pass_turn_event.send(PassTurnEvent {
-            skipping: true,
-            game_id,
-        });
-    }
-
-    true
-}
-fn draw_two_played(
-    game_id: LobbyId,
-    game: &mut Game,
-    in_uno: bool,
-    pass_turn_event: &mut EventWriter<PassTurnEvent>,
-    draw_card_event: &mut EventWriter<DrawCardEvent>,
-) -> bool {
-    for _ in 0..2 {
-        draw_card_event.send(DrawCardEvent {
-            user_key: game.players[game.next_player_index()].user_key,
-            game_id,
-            player_action: false,
-        })
-    }
-
-    if !in_uno {
-        pass_turn_event.send(PassTurnEvent {
-            skipping: true,
-            game_id,
-        });
-    }
-
-    true
-}
-
-fn skip_played(
-    game_id: LobbyId,
-    in_uno: bool,
-    pass_turn_event: &mut EventWriter<PassTurnEvent>,
-) -> bool {
-    if !in_uno {
-        pass_turn_event.send(PassTurnEvent {
-            skipping: true,
-            game_id,
-        });
-    }
-
-    true
-}
-
-fn wild_played(game: &mut Game, in_uno: bool) -> bool {
-    game.players[game.turn_index].player.state = if in_uno {
-        PlayerState::ChoosingColorWildUno {
-            uno_done: false,
-            color_chosen: false,
-        }
-    } else {
-        PlayerState::ChoosingColorWild
+    let turn_timeout = match config.turn_timeout {
+        Some(turn_timeout) => turn_timeout,
+        None => return,
     };
 
-    false
-}
+    for game in games.values_mut() {
+        let turn = game.rules.turn();
 
-fn wild_four_played(
-    game_id: LobbyId,
-    game: &mut Game,
-    in_uno: bool,
-    draw_card_event: &mut EventWriter<DrawCardEvent>,
-) -> bool {
-    game.players[game.turn_index].player.state = if in_uno {
-        PlayerState::ChoosingColorWildFourUno {
-            uno_done: false,
-            color_chosen: false,
+        if game.turn_timer.0 != turn {
+            game.turn_timer = (turn, Instant::now());
+        } else if game.turn_timer.1.elapsed() >= turn_timeout && game.rules.time_out() {
+            game.deliver(&mut server);
         }
-    } else {
-        PlayerState::ChoosingColorWildFour
-    };
-
-    let next_player = game.players[game.next_player_index()].user_key;
-    for _ in 0..4 {
-        draw_card_event.send(DrawCardEvent {
-            user_key: next_player,
-            player_action: false,
-            game_id,
-        });
     }
-
-    false
 }
 
-pub fn uno(
+pub fn game_exit(
+    mut commands: Commands,
     mut server: Server<Protocol, Channels>,
     mut games: ResMut<Games>,
-    mut uno_events: EventReader<UnoEvent>,
-    mut pass_turn_event: EventWriter<PassTurnEvent>,
+    mut game_exit_events: EventReader<GameExitEvent>,
+    mut global: ResMut<Global>,
 ) {
-    for UnoEvent { game_id, .. } in uno_events.iter() {
+    for GameExitEvent { user_key, game_id } in game_exit_events.iter() {
         let game = match games.get_mut(game_id) {
             Some(g) => g,
             None => {
-                error!("Game not found in uno");
+                error!("Game not found in game_exit");
                 continue;
             }
         };
 
-        for PlayerData { user_key, .. } in &game.players {
-            server.send_message(user_key, Channels::Uno, &StopUno::new());
-        }
-
-        let PlayerData { player, .. } = &mut game.players[game.turn_index];
-
-        let (pass_turn, skip_turn) = match player.state {
-            PlayerState::Uno => (true, false),
-            PlayerState::ChoosingColorWildUno {
-                ref mut uno_done,
-                color_chosen,
-            } => {
-                *uno_done = true;
-                (*uno_done && color_chosen, false)
-            }
-            PlayerState::ChoosingColorWildFourUno {
-                ref mut uno_done,
-                color_chosen,
-            } => {
-                *uno_done = true;
-                (*uno_done && color_chosen, true)
-            }
-            _ => {
-                error!("Player {player:?} called uno without being in Uno state");
-                (true, false)
-            }
-        };
-
-        if pass_turn {
-            if skip_turn
-                || game.discard.top().unwrap().value == Value::DrawTwo
-                || game.discard.top().unwrap().value == Value::Skip
-                || (game.players.len() == 2 && game.discard.top().unwrap().value == Value::Reverse)
-            {
-                pass_turn_event.send(PassTurnEvent {
-                    skipping: true,
-                    game_id: *game_id,
-                });
-            }
-
-            pass_turn_event.send(PassTurnEvent {
-                skipping: false,
-                game_id: *game_id,
-            });
-        }
-    }
-}
-
-pub fn counter_uno(
-    mut server: Server<Protocol, Channels>,
-    mut games: ResMut<Games>,
-    mut counter_uno_events: EventReader<CounterUnoEvent>,
-    mut draw_card_event: EventWriter<DrawCardEvent>,
-    mut pass_turn_event: EventWriter<PassTurnEvent>,
-) {
-    for CounterUnoEvent { game_id, .. } in counter_uno_events.iter() {
-        let game = match games.get_mut(game_id) {
-            Some(g) => g,
+        let seat = match game.seat(user_key) {
+            Some(seat) => seat,
             None => {
-                error!("Game not found in counter_uno");
+                error!("A user tried to leave a game they aren't playing");
                 continue;
             }
         };
 
-        for PlayerData { user_key, .. } in &game.players {
-            server.send_message(user_key, Channels::Uno, &StopUno::new());
-        }
+        game.players.remove(seat);
+        let mut player = game.rules.leave(seat).player;
+        game.deliver(&mut server);
 
-        let PlayerData {
-            player, user_key, ..
-        } = &mut game.players[game.turn_index];
-
-        let (pass_turn, skip_turn) = match player.state {
-            PlayerState::Uno => (true, false),
-            PlayerState::ChoosingColorWildUno {
-                ref mut uno_done,
-                color_chosen,
-            } => {
-                *uno_done = true;
-                (*uno_done && color_chosen, false)
-            }
-            PlayerState::ChoosingColorWildFourUno {
-                ref mut uno_done,
-                color_chosen,
-            } => {
-                *uno_done = true;
-                (*uno_done && color_chosen, true)
-            }
-            _ => {
-                error!(
-                    "Player {player:?} called counter uno when playing player wasn't in Uno state"
-                );
-                (true, false)
-            }
-        };
-
-        for _ in 0..2 {
-            draw_card_event.send(DrawCardEvent {
-                user_key: *user_key,
-                game_id: *game_id,
-                player_action: false,
-            });
-        }
+        player.hand.clear();
+        player.state = PlayerState::WaitingToPlay;
+        player.is_playing = false;
 
-        if pass_turn {
-            if skip_turn || game.discard.top().unwrap().value == Value::DrawTwo {
-                pass_turn_event.send(PassTurnEvent {
-                    skipping: true,
-                    game_id: *game_id,
-                });
-            }
-
-            pass_turn_event.send(PassTurnEvent {
-                skipping: false,
-                game_id: *game_id,
-            });
-        }
-    }
-}
-
-pub fn color_chosen(
-    mut games: ResMut<Games>,
-    mut color_chosen_events: EventReader<ColorChosenEvent>,
-    mut pass_turn_event: EventWriter<PassTurnEvent>,
-) {
-    for ColorChosenEvent { color, game_id } in color_chosen_events.iter() {
-        let game = match games.get_mut(game_id) {
-            Some(g) => g,
-            None => {
-                error!("Game not found in color_chosen");
-                continue;
-            }
-        };
-
-        let PlayerData { player, .. } = &mut game.players[game.turn_index];
-
-        let (pass_turn, skip_turn) = match player.state {
-            PlayerState::ChoosingColorWild => (true, false),
-            PlayerState::ChoosingColorWildFour => (true, true),
-            PlayerState::ChoosingColorWildUno {
-                uno_done,
-                ref mut color_chosen,
-            } => {
-                *color_chosen = true;
-                (uno_done && *color_chosen, false)
-            }
-            PlayerState::ChoosingColorWildFourUno {
-                uno_done,
-                ref mut color_chosen,
-            } => {
-                *color_chosen = true;
-                (uno_done && *color_chosen, true)
-            }
-            _ => {
-                error!("Player {player:?} changed color without being in ChoosingColor state");
-                return;
-            }
-        };
-
-        game.current_color = *color;
+        commands
+            .spawn()
+            .insert(player)
+            .insert(UserKeyComponent(*user_key));
 
-        if pass_turn {
-            if skip_turn {
-                pass_turn_event.send(PassTurnEvent {
-                    skipping: true,
-                    game_id: *game_id,
-                });
-            }
+        global.move_user(
+            &mut server,
+            user_key,
+            &global.lobbies_room_key[game_id],
+            &global.main_room_key,
+        );
 
-            pass_turn_event.send(PassTurnEvent {
-                skipping: false,
-                game_id: *game_id,
-            });
+        if game.players.is_empty() {
+            game.despawn_table(&mut server);
+            server.room_mut(&global.lobbies_room_key[game_id]).destroy();
+            global.lobbies_room_key.remove(game_id);
+            games.remove(game_id);
         }
     }
 }
 
-pub fn game_end(
+/// Bring the players back to the lobby the game was created from once all of them asked to play
+/// again, they keep their scores for the next game. The players who left the game don't have to
+/// agree
+pub fn play_again(
+    mut commands: Commands,
     mut server: Server<Protocol, Channels>,
     mut games: ResMut<Games>,
-    mut game_end_events: EventReader<GameEndEvent>,
+    mut play_again_events: EventReader<PlayAgainEvent>,
+    global: Res<Global>,
 ) {
-    for GameEndEvent { game_id } in game_end_events.iter() {
-        let game = match games.get_mut(game_id) {
-            Some(g) => g,
-            None => {
-                error!("Game not found in game_end");
-                continue;
+    for PlayAgainEvent { user_key, game_id } in play_again_events.iter() {
+        match games.get_mut(game_id) {
+            Some(game) if game.rules.is_over() && game.seat(user_key).is_some() => {
+                game.play_again.insert(*user_key);
             }
-        };
-
-        for player_data in &mut game.players {
-            player_data.player.score += player_data.player.compute_score();
-            server.send_message(&player_data.user_key, Channels::Uno, &GameEnd::new());
+            _ => {}
         }
     }
-}
 
-pub fn game_exit(
-    mut commands: Commands,
-    mut server: Server<Protocol, Channels>,
-    mut games: ResMut<Games>,
-    mut game_exit_events: EventReader<GameExitEvent>,
-    mut global: ResMut<Global>,
-    mut players_query: Query<&mut Player>,
-) {
-    for GameExitEvent { user_key, game_id } in game_exit_events.iter() {
-        let game = match games.get_mut(game_id) {
-            Some(g) => g,
-            None => {
-                error!("Game not found in game_exit");
-                continue;
-            }
-        };
+    let ready = games
+        .iter()
+        .filter(|(_, game)| {
+            !game.players.is_empty()
+                && game
+                    .players
+                    .iter()
+                    .all(|player_data| game.play_again.contains(&player_data.user_key))
+        })
+        .map(|(game_id, _)| *game_id)
+        .collect::<Vec<_>>();
+
+    for game_id in ready {
+        let mut game = games.remove(&game_id).unwrap();
+        game.despawn_table(&mut server);
 
-        for mut player in players_query.iter_mut() {
+        // The players stay in the room of the game with their private data, it becomes the room of
+        // the lobby again
+        for (player_data, seat) in game.players.iter().zip(&game.rules.seats) {
+            let mut player = seat.player.clone();
             player.hand.clear();
             player.state = PlayerState::WaitingToPlay;
+            player.is_playing = false;
+
+            commands
+                .spawn()
+                .insert(player)
+                .insert(UserKeyComponent(player_data.user_key))
+                .insert(InLobby(game_id));
+
+            server.send_message(
+                &player_data.user_key,
+                Channels::Uno,
+                &JoinLobby::new(game_id),
+            );
         }
 
-        let player_index = game
-            .players
-            .iter()
-            .position(|p| p.user_key == *user_key)
-            .unwrap();
-
-        commands
-            .spawn()
-            .insert(game.players[player_index].player.clone())
-            .insert(UserKeyComponent(*user_key));
-
-        game.players.remove(player_index);
-
-        server
-            .user_mut(user_key)
-            .leave_room(&global.lobbies_room_key[game_id])
-            .enter_room(&global.main_room_key);
-
-        server
-            .room_mut(&global.lobbies_room_key[game_id])
-            .remove_entity(&global.user_keys_entities[user_key]);
-
         server
-            .room_mut(&global.main_room_key)
-            .add_entity(&global.user_keys_entities[user_key]);
-
-        if game.players.is_empty() {
-            server.room_mut(&global.lobbies_room_key[game_id]).destroy();
-            global.lobbies_room_key.remove(game_id);
-            games.remove(game_id);
-        }
+            .spawn()
+            .enter_room(&global.main_room_key)
+            .insert(Lobby::new(game_id, game.players.len()));
     }
 }
@@ -0,0 +1,75 @@
+#![allow(clippy::too_many_arguments)]
+#![allow(clippy::type_complexity)]
+
+pub mod chat;
+pub mod config;
+pub mod events;
+pub mod game;
+pub mod lobbies;
+pub mod metrics;
+pub mod plugin;
+pub mod rate_limit;
+pub mod scope;
+pub mod server;
+
+use bevy_app::{App, ScheduleRunnerPlugin};
+use bevy_core::CorePlugin;
+use bevy_ecs::entity::Entity;
+use config::Config;
+use naia_bevy_server::{Plugin as ServerPlugin, RoomKey, Server, ServerConfig, UserKey};
+pub use plugin::{UnoServerPlugin, UnoServerSystem};
+use std::collections::{HashMap, HashSet};
+use uno::{
+    lobby::LobbyId,
+    network::{shared_config, Channels, Protocol},
+};
+
+pub struct Global {
+    pub main_room_key: RoomKey,
+    pub user_keys_entities: HashMap<UserKey, Entity>,
+    /// Entities holding the private data of each user
+    pub session_entities: HashMap<UserKey, Entity>,
+    pub lobbies_room_key: HashMap<LobbyId, RoomKey>,
+    /// Users connected only to be told that their client is out of date, they don't get a player
+    pub incompatible_users: HashSet<UserKey>,
+    /// Users to disconnect once the messages queued for them went out
+    pub disconnect_after_send: HashSet<UserKey>,
+}
+
+impl Global {
+    /// Move a user from a room to another, along with their player and their private data
+    pub fn move_user(
+        &self,
+        server: &mut Server<Protocol, Channels>,
+        user_key: &UserKey,
+        from: &RoomKey,
+        to: &RoomKey,
+    ) {
+        server.user_mut(user_key).leave_room(from).enter_room(to);
+
+        let mut entities = vec![self.user_keys_entities[user_key]];
+        entities.extend(self.session_entities.get(user_key));
+
+        for entity in &entities {
+            server.room_mut(from).remove_entity(entity);
+            server.room_mut(to).add_entity(entity);
+        }
+    }
+}
+
+/// Build the server, without running it. The binary reads its `Config` from the environment,
+/// integration tests build one listening on a free port
+pub fn app(config: Config) -> App {
+    let mut app = App::default();
+
+    app.add_plugin(CorePlugin::default())
+        .add_plugin(ScheduleRunnerPlugin::default())
+        .add_plugin(ServerPlugin::<Protocol, Channels>::new(
+            ServerConfig::default(),
+            shared_config(),
+        ))
+        .insert_resource(config)
+        .add_plugin(UnoServerPlugin);
+
+    app
+}
@@ -1,4 +1,4 @@
-use crate::{server::UserKeyComponent, Global};
+use crate::{config::Config, server::UserKeyComponent, Global};
 use bevy_core::Timer;
 use bevy_derive::{Deref, DerefMut};
 use bevy_ecs::prelude::*;
@@ -6,9 +6,11 @@ use naia_bevy_server::{Server, UserKey};
 use uno::{
     lobby::{Lobby, LobbyId},
     network::{
-        protocol::{JoinLobby, LeaveLobby, Lobby as NetworkLobby},
+        protocol::{JoinLobby, LeaveLobby, Lobby as NetworkLobby, UsernameRejected},
         Channels, Protocol,
     },
+    username::{validate_username, UsernameError},
+    Player,
 };
 
 // Events
@@ -55,24 +57,48 @@ pub fn join_lobby(
     mut server: Server<Protocol, Channels>,
     mut join_lobby_events: EventReader<JoinLobbyEvent>,
     mut lobbies_query: Query<&mut NetworkLobby>,
-    players_query: Query<(Entity, &UserKeyComponent), Without<InLobby>>,
+    players_query: Query<(Entity, &UserKeyComponent, &Player), Without<InLobby>>,
+    lobby_players_query: Query<(&Player, &InLobby)>,
     global: Res<Global>,
+    config: Res<Config>,
 ) {
     for JoinLobbyEvent { lobby_id, user_key } in join_lobby_events.iter() {
-        server
-            .user_mut(user_key)
-            .leave_room(&global.main_room_key)
-            .enter_room(&global.lobbies_room_key[lobby_id]);
+        // Users already in a lobby or in a game have to leave it first
+        let player = match players_query
+            .iter()
+            .find(|(_, player_user_key, _)| ***player_user_key == *user_key)
+        {
+            Some((_, _, player)) => player,
+            None => continue,
+        };
+
+        // The username stays empty until the user sends a valid one
+        if let Err(error) = validate_username(&player.username, &config.username_blocklist) {
+            server.send_message(user_key, Channels::Uno, &UsernameRejected::new(error));
+            continue;
+        }
 
-        server
-            .room_mut(&global.main_room_key)
-            .remove_entity(&global.user_keys_entities[user_key]);
+        let username_taken = lobby_players_query.iter().any(|(lobby_player, in_lobby)| {
+            **in_lobby == *lobby_id && lobby_player.username == player.username
+        });
+
+        if username_taken {
+            server.send_message(
+                user_key,
+                Channels::Uno,
+                &UsernameRejected::new(UsernameError::AlreadyTaken),
+            );
+            continue;
+        }
 
-        server
-            .room_mut(&global.lobbies_room_key[lobby_id])
-            .add_entity(&global.user_keys_entities[user_key]);
+        global.move_user(
+            &mut server,
+            user_key,
+            &global.main_room_key,
+            &global.lobbies_room_key[lobby_id],
+        );
 
-        for (entity, &player_user_key) in players_query.iter() {
+        for (entity, &player_user_key, _) in players_query.iter() {
             if *player_user_key == *user_key {
                 commands.entity(entity).insert(InLobby(*lobby_id));
                 break;
@@ -98,18 +124,12 @@ pub fn leave_lobby(
     global: Res<Global>,
 ) {
     for LeaveLobbyEvent { user_key, lobby_id } in leave_lobby_events.iter() {
-        server
-            .user_mut(user_key)
-            .leave_room(&global.lobbies_room_key[lobby_id])
-            .enter_room(&global.main_room_key);
-
-        server
-            .room_mut(&global.lobbies_room_key[lobby_id])
-            .remove_entity(&global.user_keys_entities[user_key]);
-
-        server
-            .room_mut(&global.main_room_key)
-            .add_entity(&global.user_keys_entities[user_key]);
+        global.move_user(
+            &mut server,
+            user_key,
+            &global.lobbies_room_key[lobby_id],
+            &global.main_room_key,
+        );
 
         for (entity, &player_user_key) in players_query.iter() {
             if *player_user_key == *user_key {
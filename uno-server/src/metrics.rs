@@ -0,0 +1,238 @@
+//! Counters and gauges of a running server, served in the Prometheus text format on
+//! `http://<UNO_METRICS_ADDR>/metrics` when the address is configured
+
+use crate::{
+    config::Config,
+    game::Games,
+    rate_limit::{MessageKind, RateLimiter},
+    Global,
+};
+use bevy_core::Time;
+use bevy_ecs::prelude::*;
+use bevy_log::{error, info};
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+use uno::network::protocol::Lobby;
+
+/// Time between two updates of the page served by the endpoint
+const PUBLISH_INTERVAL_S: f64 = 1.0;
+/// Time a scraper has to send its request, or to take the response
+const CONNECTION_TIMEOUT: Duration = Duration::from_millis(500);
+
+#[derive(Default)]
+pub struct Metrics {
+    pub connected_users: usize,
+    pub active_lobbies: usize,
+    pub active_games: usize,
+    /// Messages received from the clients, by type, including the ones dropped by the rate
+    /// limiter
+    pub messages: HashMap<MessageKind, u64>,
+    /// Game actions that were refused, like a card that can't be played
+    pub rejected_actions: u64,
+    pub games_finished: u64,
+    /// Time spent replicating the state to the clients during the last tick
+    pub tick_duration: Duration,
+}
+
+impl Metrics {
+    /// The metrics in the Prometheus text format
+    pub fn render(&self, rate_limiter: &RateLimiter) -> String {
+        let mut text = String::new();
+
+        let mut metric = |name: &str, kind: &str, help: &str, values: Vec<(String, String)>| {
+            let _ = writeln!(text, "# HELP {name} {help}");
+            let _ = writeln!(text, "# TYPE {name} {kind}");
+            for (labels, value) in values {
+                let _ = writeln!(text, "{name}{labels} {value}");
+            }
+        };
+        let single = |value: String| vec![(String::new(), value)];
+        let by_kind = |counts: &HashMap<MessageKind, u64>| {
+            let mut values = counts
+                .iter()
+                .map(|(kind, count)| (format!("{{kind=\"{kind:?}\"}}"), count.to_string()))
+                .collect::<Vec<_>>();
+            values.sort();
+            values
+        };
+
+        metric(
+            "uno_connected_users",
+            "gauge",
+            "Users connected to the server",
+            single(self.connected_users.to_string()),
+        );
+        metric(
+            "uno_active_lobbies",
+            "gauge",
+            "Lobbies waiting for their game to start",
+            single(self.active_lobbies.to_string()),
+        );
+        metric(
+            "uno_active_games",
+            "gauge",
+            "Games being played or showing their results",
+            single(self.active_games.to_string()),
+        );
+        metric(
+            "uno_messages_received_total",
+            "counter",
+            "Messages received from the clients",
+            by_kind(&self.messages),
+        );
+        metric(
+            "uno_messages_dropped_total",
+            "counter",
+            "Messages dropped by the rate limiter",
+            by_kind(&rate_limiter.dropped_messages),
+        );
+        metric(
+            "uno_rejected_actions_total",
+            "counter",
+            "Game actions refused by the server",
+            single(self.rejected_actions.to_string()),
+        );
+        metric(
+            "uno_games_finished_total",
+            "counter",
+            "Games played until someone had no cards left",
+            single(self.games_finished.to_string()),
+        );
+        metric(
+            "uno_tick_duration_seconds",
+            "gauge",
+            "Duration of the last server tick",
+            single(self.tick_duration.as_secs_f64().to_string()),
+        );
+
+        text
+    }
+}
+
+/// Last rendering of the metrics, shared with the thread answering the HTTP requests
+#[derive(Clone, Default)]
+pub struct MetricsPage(Arc<Mutex<String>>);
+
+pub fn start_metrics_endpoint(mut commands: Commands, config: Res<Config>) {
+    let address = match config.metrics_address {
+        Some(address) => address,
+        None => return,
+    };
+
+    let listener = match TcpListener::bind(address) {
+        Ok(listener) => listener,
+        Err(err) => {
+            error!("Couldn't serve the metrics on {address}: {err}");
+            return;
+        }
+    };
+    info!("serving metrics on http://{address}/metrics");
+
+    let page = MetricsPage::default();
+    let thread_page = page.clone();
+    thread::spawn(move || {
+        // Each connection gets its own thread, so that a slow client doesn't hold the others
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let page = thread_page.clone();
+                    thread::spawn(move || answer(stream, &page));
+                }
+                Err(err) => error!("Metrics connection failed: {err}"),
+            }
+        }
+    });
+
+    commands.insert_resource(page);
+}
+
+fn answer(mut stream: TcpStream, page: &MetricsPage) {
+    let _ = stream.set_read_timeout(Some(CONNECTION_TIMEOUT));
+    let _ = stream.set_write_timeout(Some(CONNECTION_TIMEOUT));
+
+    // Only the request line matters, the headers are read until the blank line ending them
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    let mut header = String::new();
+    while let Ok(1..) = reader.read_line(&mut header) {
+        if header.trim().is_empty() {
+            break;
+        }
+        header.clear();
+    }
+
+    let response = if request_line.starts_with("GET /metrics ") {
+        let body = page.0.lock().unwrap().clone();
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    } else {
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_owned()
+    };
+
+    let _ = stream.write_all(response.as_bytes());
+}
+
+/// Update the gauges and the page of the endpoint, if it is running
+pub fn publish_metrics(
+    mut metrics: ResMut<Metrics>,
+    page: Option<Res<MetricsPage>>,
+    rate_limiter: Res<RateLimiter>,
+    global: Res<Global>,
+    games: Res<Games>,
+    lobbies_query: Query<&Lobby>,
+    time: Res<Time>,
+    mut last_publish: Local<Option<f64>>,
+) {
+    let page = match page {
+        Some(page) => page,
+        None => return,
+    };
+
+    let now = time.seconds_since_startup();
+    if matches!(*last_publish, Some(last) if now - last < PUBLISH_INTERVAL_S) {
+        return;
+    }
+    *last_publish = Some(now);
+
+    metrics.connected_users = global.user_keys_entities.len();
+    metrics.active_lobbies = lobbies_query.iter().count();
+    metrics.active_games = games.len();
+
+    *page.0.lock().unwrap() = metrics.render(&rate_limiter);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prometheus_text_format() {
+        let mut rate_limiter = RateLimiter::default();
+        rate_limiter.dropped_messages.insert(MessageKind::Chat, 3);
+
+        let metrics = Metrics {
+            games_finished: 2,
+            messages: HashMap::from([(MessageKind::GameAction, 12)]),
+            ..Metrics::default()
+        };
+        let text = metrics.render(&rate_limiter);
+
+        assert!(
+            text.contains("# TYPE uno_games_finished_total counter\nuno_games_finished_total 2\n")
+        );
+        assert!(text.contains("uno_messages_received_total{kind=\"GameAction\"} 12\n"));
+        assert!(text.contains("uno_messages_dropped_total{kind=\"Chat\"} 3\n"));
+    }
+}
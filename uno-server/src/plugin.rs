@@ -0,0 +1,163 @@
+use crate::{chat, config::Config, events, game, lobbies, metrics, rate_limit, server};
+use bevy_app::{App, CoreStage, Plugin};
+use bevy_ecs::schedule::{ParallelSystemDescriptorCoercion, SystemLabel};
+use naia_bevy_server::Stage;
+use std::collections::HashMap;
+
+/// Labels of the server systems, to order them and to let the app embedding the server run
+/// its own systems before or after them
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum UnoServerSystem {
+    /// Network events: authorization, connections and messages
+    ReceiveEvents,
+    /// Replication of the game state to the clients
+    Tick,
+    /// Username changes
+    Players,
+    /// Lobbies creation, joining and leaving
+    Lobbies,
+    Chat,
+    /// Creation of the games, in `CoreStage::PreUpdate`
+    SetupGame,
+    /// Actions of the players, applied by the rules of their game
+    GameActions,
+    /// Turns running out of time, after the actions of the players
+    TurnTimer,
+    /// Players leaving their game, in `CoreStage::PostUpdate`
+    GameExit,
+    /// Players going back to the lobby of their game, in `CoreStage::PostUpdate` after the
+    /// players who left, so that they don't have to agree
+    PlayAgain,
+    /// Update of the metrics served to the operators, in `CoreStage::Last`
+    Metrics,
+}
+
+/// All the game logic of the server. The transport isn't part of it: the app must add the naia
+/// `Plugin` itself, and can insert a `Config` before adding this plugin (it is read from the
+/// environment otherwise).
+///
+/// naia has no mock transport, so the systems always need a naia server listening on a local
+/// address. Tests that don't need clients drive the logic by sending the plugin's events, like
+/// `CreateLobbyEvent`, straight into the app
+pub struct UnoServerPlugin;
+
+impl Plugin for UnoServerPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.world.contains_resource::<Config>() {
+            app.insert_resource(Config::from_env());
+        }
+
+        app.add_startup_system(server::server_init)
+            // Metrics
+            .init_resource::<metrics::Metrics>()
+            .add_startup_system(metrics::start_metrics_endpoint)
+            .add_system_to_stage(
+                CoreStage::Last,
+                metrics::publish_metrics.label(UnoServerSystem::Metrics),
+            )
+            // Server
+            .add_event::<server::UsernameChangedEvent>()
+            .init_resource::<rate_limit::RateLimiter>()
+            .add_system_to_stage(Stage::Tick, server::tick.label(UnoServerSystem::Tick))
+            .add_system(server::username_updated.label(UnoServerSystem::Players))
+            // Events
+            .add_system_to_stage(
+                Stage::ReceiveEvents,
+                events::authorization_event.label(UnoServerSystem::ReceiveEvents),
+            )
+            .add_system_to_stage(
+                Stage::ReceiveEvents,
+                events::connection_event.label(UnoServerSystem::ReceiveEvents),
+            )
+            .add_system_to_stage(
+                Stage::ReceiveEvents,
+                events::disconnection_event.label(UnoServerSystem::ReceiveEvents),
+            )
+            .add_system_to_stage(
+                Stage::ReceiveEvents,
+                events::message_event.label(UnoServerSystem::ReceiveEvents),
+            )
+            // Lobbies
+            .add_event::<lobbies::CreateLobbyEvent>()
+            .add_event::<lobbies::JoinLobbyEvent>()
+            .add_event::<lobbies::LeaveLobbyEvent>()
+            .add_system(
+                lobbies::create_lobby
+                    .label(UnoServerSystem::Lobbies)
+                    .after(UnoServerSystem::Players),
+            )
+            .add_system(
+                lobbies::join_lobby
+                    .label(UnoServerSystem::Lobbies)
+                    .after(UnoServerSystem::Players),
+            )
+            .add_system(
+                lobbies::leave_lobby
+                    .label(UnoServerSystem::Lobbies)
+                    .after(UnoServerSystem::Players),
+            )
+            // Chat
+            .add_event::<chat::ChatEvent>()
+            .add_system(
+                chat::chat
+                    .label(UnoServerSystem::Chat)
+                    .after(UnoServerSystem::Lobbies),
+            )
+            // Game
+            .insert_resource(game::Games(HashMap::new()))
+            .add_event::<game::StartGameEvent>()
+            .add_event::<game::GameActionEvent>()
+            .add_event::<game::GameExitEvent>()
+            .add_event::<game::PlayAgainEvent>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                game::setup_game.label(UnoServerSystem::SetupGame),
+            )
+            .add_system(
+                game::game_action
+                    .label(UnoServerSystem::GameActions)
+                    .after(UnoServerSystem::Lobbies),
+            )
+            .add_system(
+                game::turn_timer
+                    .label(UnoServerSystem::TurnTimer)
+                    .after(UnoServerSystem::GameActions),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                game::game_exit.label(UnoServerSystem::GameExit),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                game::play_again
+                    .label(UnoServerSystem::PlayAgain)
+                    .after(UnoServerSystem::GameExit),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{config::Config, lobbies::CreateLobbyEvent};
+    use bevy_ecs::event::Events;
+    use std::net::TcpListener;
+    use uno::network::protocol::Lobby;
+
+    #[test]
+    fn events_drive_the_plugin_without_clients() {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .unwrap()
+            .port();
+        let mut app = crate::app(Config::local(port));
+        app.update();
+
+        app.world
+            .resource_mut::<Events<CreateLobbyEvent>>()
+            .send(CreateLobbyEvent);
+        app.update();
+
+        let mut lobbies = app.world.query::<&Lobby>();
+        assert_eq!(lobbies.iter(&app.world).count(), 1);
+    }
+}
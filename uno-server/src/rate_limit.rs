@@ -0,0 +1,259 @@
+use naia_bevy_server::UserKey;
+use std::{collections::HashMap, hash::Hash, net::IpAddr, time::Duration};
+use uno::network::Protocol;
+
+/// Number of strikes after which a user is disconnected and banned
+pub const BAN_THRESHOLD: f32 = 20.0;
+/// Number of strikes forgiven every second
+const STRIKE_DECAY_PER_S: f32 = 0.5;
+
+/// Group of client messages sharing the same rate limit
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MessageKind {
+    CreateLobby,
+    JoinLeaveLobby,
+    Username,
+    StartGame,
+    GameAction,
+    Chat,
+    Other,
+}
+
+impl MessageKind {
+    pub fn of(protocol: &Protocol) -> MessageKind {
+        match protocol {
+            Protocol::CreateLobby(_) => MessageKind::CreateLobby,
+            Protocol::JoinLobby(_) | Protocol::LeaveLobby(_) => MessageKind::JoinLeaveLobby,
+            Protocol::Username(_) => MessageKind::Username,
+            Protocol::StartGame(_) => MessageKind::StartGame,
+            Protocol::GameAction(_) => MessageKind::GameAction,
+            Protocol::SendChat(_) => MessageKind::Chat,
+            _ => MessageKind::Other,
+        }
+    }
+
+    /// Size of the bucket and number of tokens refilled every second
+    fn limits(&self) -> (f32, f32) {
+        match self {
+            MessageKind::CreateLobby => (3.0, 0.1),
+            MessageKind::JoinLeaveLobby => (5.0, 1.0),
+            MessageKind::Username => (5.0, 1.0),
+            MessageKind::StartGame => (3.0, 0.5),
+            MessageKind::GameAction => (20.0, 5.0),
+            MessageKind::Chat => (5.0, 1.0),
+            MessageKind::Other => (10.0, 2.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    tokens: f32,
+    capacity: f32,
+    refill_per_s: f32,
+    last_refill: f64,
+}
+
+impl TokenBucket {
+    /// Create a full bucket
+    pub fn new(capacity: f32, refill_per_s: f32, now: f64) -> TokenBucket {
+        TokenBucket {
+            tokens: capacity,
+            capacity,
+            refill_per_s,
+            last_refill: now,
+        }
+    }
+
+    /// Take a token from the bucket if there is one left
+    pub fn try_take(&mut self, now: f64) -> bool {
+        let elapsed = (now - self.last_refill).max(0.0) as f32;
+        self.tokens = (self.tokens + elapsed * self.refill_per_s).min(self.capacity);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Allow,
+    Drop,
+    Ban,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Strikes {
+    count: f32,
+    last_strike: f64,
+}
+
+/// Rate limits of every user. Users are identified by their `UserKey` on the server, the tests
+/// use plain numbers
+pub struct RateLimiter<K = UserKey> {
+    buckets: HashMap<(K, MessageKind), TokenBucket>,
+    strikes: HashMap<K, Strikes>,
+    /// Time at which the ban of each banned address ends
+    banned_addresses: HashMap<IpAddr, f64>,
+    /// Number of messages dropped since the server started, by type of message
+    pub dropped_messages: HashMap<MessageKind, u64>,
+}
+
+impl<K> Default for RateLimiter<K> {
+    fn default() -> Self {
+        RateLimiter {
+            buckets: HashMap::new(),
+            strikes: HashMap::new(),
+            banned_addresses: HashMap::new(),
+            dropped_messages: HashMap::new(),
+        }
+    }
+}
+
+impl<K: Copy + Eq + Hash> RateLimiter<K> {
+    /// Decide what to do with a message a user just sent
+    pub fn check(&mut self, user_key: K, kind: MessageKind, now: f64) -> Verdict {
+        let (capacity, refill_per_s) = kind.limits();
+        let bucket = self
+            .buckets
+            .entry((user_key, kind))
+            .or_insert_with(|| TokenBucket::new(capacity, refill_per_s, now));
+
+        if bucket.try_take(now) {
+            return Verdict::Allow;
+        }
+
+        *self.dropped_messages.entry(kind).or_insert(0) += 1;
+
+        let strikes = self.strikes.entry(user_key).or_insert(Strikes {
+            count: 0.0,
+            last_strike: now,
+        });
+        let forgiven = (now - strikes.last_strike).max(0.0) as f32 * STRIKE_DECAY_PER_S;
+        strikes.count = (strikes.count - forgiven).max(0.0) + 1.0;
+        strikes.last_strike = now;
+
+        if strikes.count >= BAN_THRESHOLD {
+            Verdict::Ban
+        } else {
+            Verdict::Drop
+        }
+    }
+
+    /// Remove everything known about a user once they disconnect
+    pub fn forget(&mut self, user_key: &K) {
+        self.buckets.retain(|(key, _), _| key != user_key);
+        self.strikes.remove(user_key);
+    }
+
+    /// Refuse the connections from `address` for `duration`
+    pub fn ban(&mut self, address: IpAddr, duration: Duration, now: f64) {
+        self.banned_addresses.retain(|_, until| *until > now);
+        self.banned_addresses
+            .insert(address, now + duration.as_secs_f64());
+    }
+
+    pub fn is_banned(&self, address: &IpAddr, now: f64) -> bool {
+        self.banned_addresses
+            .get(address)
+            .is_some_and(|until| *until > now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn bucket_empties_then_refills() {
+        let mut bucket = TokenBucket::new(2.0, 1.0, 0.0);
+
+        assert!(bucket.try_take(0.0));
+        assert!(bucket.try_take(0.0));
+        assert!(!bucket.try_take(0.5));
+        assert!(bucket.try_take(1.0));
+    }
+
+    #[test]
+    fn bucket_does_not_overflow() {
+        let mut bucket = TokenBucket::new(2.0, 1.0, 0.0);
+
+        assert!(bucket.try_take(100.0));
+        assert!(bucket.try_take(100.0));
+        assert!(!bucket.try_take(100.0));
+    }
+
+    #[test]
+    fn flooding_user_gets_banned() {
+        let mut rate_limiter = RateLimiter::default();
+        let (capacity, _) = MessageKind::CreateLobby.limits();
+
+        for _ in 0..capacity as usize {
+            assert_eq!(
+                rate_limiter.check(1, MessageKind::CreateLobby, 0.0),
+                Verdict::Allow
+            );
+        }
+        for _ in 1..BAN_THRESHOLD as usize {
+            assert_eq!(
+                rate_limiter.check(1, MessageKind::CreateLobby, 0.0),
+                Verdict::Drop
+            );
+        }
+        assert_eq!(
+            rate_limiter.check(1, MessageKind::CreateLobby, 0.0),
+            Verdict::Ban
+        );
+
+        // Other users keep their own limits
+        assert_eq!(
+            rate_limiter.check(2, MessageKind::CreateLobby, 0.0),
+            Verdict::Allow
+        );
+        assert_eq!(
+            rate_limiter.dropped_messages[&MessageKind::CreateLobby],
+            BAN_THRESHOLD as u64
+        );
+    }
+
+    #[test]
+    fn strikes_are_forgiven_over_time() {
+        let mut rate_limiter = RateLimiter::default();
+        let (capacity, _) = MessageKind::StartGame.limits();
+
+        for _ in 0..capacity as usize {
+            rate_limiter.check(1, MessageKind::StartGame, 0.0);
+        }
+
+        // A dropped message every 2 seconds is forgiven as fast as it is counted
+        for step in 0..100 {
+            let now = step as f64 * 2.0;
+            let verdict = loop {
+                match rate_limiter.check(1, MessageKind::StartGame, now) {
+                    Verdict::Allow => {}
+                    verdict => break verdict,
+                }
+            };
+            assert_eq!(verdict, Verdict::Drop);
+        }
+    }
+
+    #[test]
+    fn bans_expire() {
+        let mut rate_limiter = RateLimiter::<u32>::default();
+        let address = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+        rate_limiter.ban(address, Duration::from_secs(60), 10.0);
+
+        assert!(rate_limiter.is_banned(&address, 10.0));
+        assert!(rate_limiter.is_banned(&address, 69.0));
+        assert!(!rate_limiter.is_banned(&address, 70.0));
+        assert!(!rate_limiter.is_banned(&IpAddr::V4(Ipv4Addr::UNSPECIFIED), 10.0));
+    }
+}
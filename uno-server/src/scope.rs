@@ -0,0 +1,25 @@
+use bevy_derive::{Deref, DerefMut};
+use bevy_ecs::prelude::*;
+use naia_bevy_server::UserKey;
+
+/// Marks a server entity whose replicated components must only be sent to one user
+#[derive(Component, Deref, DerefMut, Copy, Clone)]
+pub struct PrivateTo(pub UserKey);
+
+/// Whether an entity of a room should be replicated to a user of the same room. Private entities
+/// are only ever replicated to their owner, the others to everyone in the room
+pub fn is_in_scope<K: PartialEq>(user: &K, owner: Option<&K>) -> bool {
+    owner.map_or(true, |owner| owner == user)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_in_scope;
+
+    #[test]
+    fn private_entities_only_reach_their_owner() {
+        assert!(is_in_scope(&1, None));
+        assert!(is_in_scope(&1, Some(&1)));
+        assert!(!is_in_scope(&2, Some(&1)));
+    }
+}
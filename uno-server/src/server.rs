@@ -1,14 +1,25 @@
-use crate::{game::Games, Global};
+use crate::{
+    config::Config,
+    game::Games,
+    lobbies::InLobby,
+    metrics::Metrics,
+    scope::{is_in_scope, PrivateTo},
+    Global,
+};
 use bevy_derive::{Deref, DerefMut};
 use bevy_ecs::prelude::*;
 use bevy_log::info;
 use naia_bevy_server::{Server, ServerAddrs, UserKey};
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    time::Instant,
+};
 use uno::{
     network::{
-        protocol::{CurrentColor, Player as NetworkPlayer},
-        Channels, Protocol,
+        protocol::{CurrentColor, Player as NetworkPlayer, TableState, UsernameRejected},
+        set_if_changed, Channels, Protocol,
     },
+    username::{validate_username, UsernameError},
     Player,
 };
 
@@ -20,13 +31,17 @@ pub struct UsernameChangedEvent {
 #[derive(Component, Deref, DerefMut, Copy, Clone)]
 pub struct UserKeyComponent(pub UserKey);
 
-pub fn server_init(mut commands: Commands, mut server: Server<Protocol, Channels>) {
-    info!("init server");
+pub fn server_init(
+    mut commands: Commands,
+    mut server: Server<Protocol, Channels>,
+    config: Res<Config>,
+) {
+    info!("init server on {}", config.session_address);
 
     let server_addresses = ServerAddrs::new(
-        "127.0.0.1:3478".parse().unwrap(),
-        "127.0.0.1:3478".parse().unwrap(),
-        "http://127.0.0.1:3478",
+        config.session_address,
+        config.webrtc_address,
+        &config.public_webrtc_url,
     );
 
     server.listen(&server_addresses);
@@ -34,25 +49,33 @@ pub fn server_init(mut commands: Commands, mut server: Server<Protocol, Channels
     commands.insert_resource(Global {
         main_room_key: server.make_room().key(),
         user_keys_entities: HashMap::new(),
+        session_entities: HashMap::new(),
         lobbies_room_key: HashMap::new(),
+        incompatible_users: HashSet::new(),
+        disconnect_after_send: HashSet::new(),
     });
 }
 
 pub fn tick(
     mut server: Server<Protocol, Channels>,
     mut network_players_query: Query<(Entity, &mut NetworkPlayer)>,
-    mut current_color_query: Query<(Entity, &mut CurrentColor)>,
+    mut table_query: Query<(&mut CurrentColor, &mut TableState)>,
     players_query: Query<(&UserKeyComponent, &Player)>,
+    private_query: Query<&PrivateTo>,
     global: Res<Global>,
     games: Res<Games>,
+    mut metrics: ResMut<Metrics>,
 ) {
+    let start = Instant::now();
+
     // Sync player number of cards, score with clients
     for (entity, mut network_player) in network_players_query.iter_mut() {
         for (_, game) in games.iter() {
-            if let Some(player_data) = game.players.iter().find(|p| p.server_entity == entity) {
-                *network_player.hand_size = player_data.player.hand.len();
-                *network_player.score = player_data.player.score;
-                *network_player.is_playing = player_data.player.is_playing;
+            if let Some(seat) = game.players.iter().position(|p| p.server_entity == entity) {
+                let player = &game.rules.seats[seat].player;
+                set_if_changed(&mut network_player.hand_size, player.hand.len());
+                set_if_changed(&mut network_player.score, player.score);
+                set_if_changed(&mut network_player.is_playing, player.is_playing);
             }
         }
     }
@@ -61,62 +84,99 @@ pub fn tick(
     for (user_key, player) in players_query.iter() {
         for (entity, mut network_player) in network_players_query.iter_mut() {
             if entity == global.user_keys_entities[user_key] {
-                *network_player.username = player.username.clone();
+                set_if_changed(&mut network_player.username, player.username.clone());
             }
         }
     }
 
-    // Sync current color
-    for (game_id, game) in games.iter() {
-        for (entity, mut current_color) in current_color_query.iter_mut() {
-            if server
-                .room(&global.lobbies_room_key[game_id])
-                .has_entity(&entity)
-            {
-                *current_color.color = game.current_color as u8;
-            }
-        }
+    // Sync current color, direction of play, deck sizes and current player
+    for game in games.values() {
+        let current_player_id = game
+            .current_player()
+            .and_then(|player_data| network_players_query.get(player_data.server_entity).ok())
+            .map(|(_, network_player)| *network_player.id)
+            .unwrap_or(0);
+
+        let (mut current_color, mut table_state) = match game
+            .table_entity
+            .and_then(|entity| table_query.get_mut(entity).ok())
+        {
+            Some(table) => table,
+            None => continue,
+        };
+
+        let rules = &game.rules;
+        set_if_changed(&mut current_color.color, rules.current_color() as u8);
+        set_if_changed(&mut table_state.reverse_turn, rules.reverse_turn());
+        set_if_changed(&mut table_state.draw_pile_size, rules.draw_pile_size());
+        set_if_changed(&mut table_state.discard_size, rules.discard_size());
+        set_if_changed(&mut table_state.current_player, current_player_id);
     }
 
-    for (_room_key, user_key, entity) in server.scope_checks() {
-        /*
-                if !server.room(&room_key).has_entity(&entity) {
-                    break;
-                }
-
-                let mut include = true;
-
-                if let Some(p) = server.entity(&entity).component::<ThisPlayer>() {
-                    for (entity, player) in network_players_query.iter() {
-                        if *player.id == *p.id && entity == global.user_keys_entities[&user_key] {
-                            include = true;
-        FR
-                            break;
-                        } else {
-                            include = false;
-                        }
-                    }
-                    dbg!(include, *p.id);
-                }
-
-                if include {
-                    server.user_scope(&user_key).include(&entity);
-                } else {
-                    server.user_scope(&user_key).exclude(&entity);
-                }
-                */
-        server.user_scope(&user_key).include(&entity);
+    // Only the users sharing a room with an entity are checked
+    for (_, user_key, entity) in server.scope_checks() {
+        let owner = private_query
+            .get(entity)
+            .ok()
+            .map(|private_to| &private_to.0);
+
+        if is_in_scope(&user_key, owner) {
+            server.user_scope(&user_key).include(&entity);
+        } else {
+            server.user_scope(&user_key).exclude(&entity);
+        }
     }
 
     server.send_all_updates();
+
+    for user_key in global.disconnect_after_send.drain() {
+        if server.user_exists(&user_key) {
+            server.user_mut(&user_key).disconnect();
+        }
+    }
+
+    metrics.tick_duration = start.elapsed();
 }
 
 pub fn username_updated(
+    mut server: Server<Protocol, Channels>,
     mut username_changed_events: EventReader<UsernameChangedEvent>,
-    mut players_query: Query<(&UserKeyComponent, &mut Player)>,
+    mut players_query: Query<(&UserKeyComponent, &mut Player, Option<&InLobby>)>,
+    config: Res<Config>,
 ) {
     for UsernameChangedEvent { user_key, username } in username_changed_events.iter() {
-        for (player_user_key, mut player) in players_query.iter_mut() {
+        let username = match validate_username(username, &config.username_blocklist) {
+            Ok(username) => username,
+            Err(error) => {
+                server.send_message(user_key, Channels::Uno, &UsernameRejected::new(error));
+                continue;
+            }
+        };
+
+        let user_lobby = players_query
+            .iter()
+            .find(|(player_user_key, _, _)| ***player_user_key == *user_key)
+            .and_then(|(_, _, in_lobby)| in_lobby.map(|in_lobby| **in_lobby));
+
+        let already_taken = user_lobby.is_some()
+            && players_query
+                .iter()
+                .any(|(player_user_key, player, in_lobby)| {
+                    ***player_user_key != *user_key
+                        && in_lobby.map(|in_lobby| **in_lobby) == user_lobby
+                        && player.username == username
+                });
+
+        if already_taken {
+            server.send_message(
+                user_key,
+                Channels::Uno,
+                &UsernameRejected::new(UsernameError::AlreadyTaken),
+            );
+            continue;
+        }
+
+        for (player_user_key, mut player, _) in players_query.iter_mut() {
             if **player_user_key == *user_key {
                 player.username = username.clone();
                 break;
@@ -0,0 +1,357 @@
+//! A headless client driving the server with blocking calls, to write integration tests
+//! playing scripted games
+
+use naia_client::{Client, ClientConfig, Event};
+use naia_shared::{Protocolize, Replicate, ReplicateSafe};
+use std::{
+    collections::VecDeque,
+    fmt, thread,
+    time::{Duration, Instant},
+};
+use uno::{
+    card::Color,
+    lobby::LobbyId,
+    network::{
+        protocol::{
+            CardValidation, CreateLobby, CurrentColor, DrawCard, GameAction, Handshake, JoinLobby,
+            LeaveLobby, Lobby, Player, StartGame, Username, YourPlayerId,
+        },
+        shared_config,
+        world::{Entity, World},
+        Channels, Protocol,
+    },
+    Card,
+};
+
+/// How long to wait for the server before giving up
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+#[derive(Debug)]
+pub enum TestClientError {
+    /// The server didn't send what was expected in time
+    Timeout(String),
+    Rejected,
+    /// The server runs another version of the protocol
+    Incompatible {
+        server_version: String,
+    },
+    Disconnected,
+    Naia(String),
+}
+
+impl fmt::Display for TestClientError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            TestClientError::Timeout(what) => write!(f, "timed out waiting for {what}"),
+            TestClientError::Rejected => write!(f, "the server rejected the connection"),
+            TestClientError::Incompatible { server_version } => {
+                write!(
+                    f,
+                    "the server runs the incompatible version {server_version}"
+                )
+            }
+            TestClientError::Disconnected => write!(f, "the server closed the connection"),
+            TestClientError::Naia(err) => write!(f, "network error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for TestClientError {}
+
+pub type Result<T> = std::result::Result<T, TestClientError>;
+
+pub struct TestClient {
+    client: Client<Protocol, Entity, Channels>,
+    world: World<Protocol>,
+    /// Messages received and not expected yet, oldest first
+    messages: VecDeque<Protocol>,
+    hand: Vec<Card>,
+    top_discard: Option<Card>,
+    connected: bool,
+    pub timeout: Duration,
+}
+
+impl TestClient {
+    /// Connect to the server at `address`, like `http://127.0.0.1:3478`
+    pub fn connect(address: &str) -> Result<TestClient> {
+        TestClient::connect_with(
+            address,
+            Handshake::new(env!("CARGO_PKG_VERSION").to_owned()),
+        )
+    }
+
+    /// Keep trying to connect until the server answers, for a server that may still be starting
+    pub fn connect_when_ready(address: &str) -> Result<TestClient> {
+        let deadline = Instant::now() + DEFAULT_TIMEOUT;
+
+        loop {
+            match TestClient::connect(address) {
+                Ok(client) => return Ok(client),
+                Err(err) if Instant::now() > deadline => return Err(err),
+                Err(_) => thread::sleep(POLL_INTERVAL),
+            }
+        }
+    }
+
+    /// Connect with a custom handshake, to test how the server handles other clients
+    pub fn connect_with(address: &str, handshake: Handshake) -> Result<TestClient> {
+        let mut test_client = TestClient::open(address, handshake, ClientConfig::default());
+        test_client.wait_until("the connection", |client| client.connected)?;
+        test_client.wait_until("the player id", |client| client.player_id().is_some())?;
+
+        Ok(test_client)
+    }
+
+    /// Start connecting without waiting for the server, to test what happens before the client
+    /// gets a player
+    pub fn open(address: &str, handshake: Handshake, config: ClientConfig) -> TestClient {
+        let mut client = Client::new(&config, &shared_config());
+        client.auth(handshake);
+        client.connect(address);
+
+        TestClient {
+            client,
+            world: World::default(),
+            messages: VecDeque::new(),
+            hand: vec![],
+            top_discard: None,
+            connected: false,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    pub fn disconnect(mut self) {
+        self.client.disconnect();
+    }
+
+    /// Receive everything the server sent since the last update
+    pub fn update(&mut self) -> Result<()> {
+        for event in self.client.receive(self.world.proxy_mut()) {
+            match event {
+                Ok(Event::Connection(_)) => self.connected = true,
+                Ok(Event::Rejection(_)) => return Err(TestClientError::Rejected),
+                Ok(Event::Disconnection(_)) => return Err(TestClientError::Disconnected),
+                Ok(Event::Message(_, Protocol::IncompatibleVersion(incompatible))) => {
+                    return Err(TestClientError::Incompatible {
+                        server_version: (*incompatible.server_version).clone(),
+                    })
+                }
+                Ok(Event::Message(_, message)) => {
+                    self.track(&message);
+                    self.messages.push_back(message);
+                }
+                Ok(_) => {}
+                Err(err) => return Err(TestClientError::Naia(format!("{err:?}"))),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Keep the hand and the discard up to date
+    fn track(&mut self, message: &Protocol) {
+        match message {
+            Protocol::StartGame(_) => {
+                self.hand.clear();
+                self.top_discard = None;
+            }
+            Protocol::DrawCard(card) => self.hand.push((*card.color, *card.value).into()),
+            Protocol::CardPlayed(card) => {
+                self.top_discard = Some((*card.color, *card.value).into())
+            }
+            Protocol::GameSnapshot(snapshot) => {
+                self.hand = snapshot.hand();
+                self.top_discard = Some(snapshot.top_discard());
+            }
+            _ => {}
+        }
+    }
+
+    /// Update until `condition` holds, or fail after the timeout
+    pub fn wait_until(
+        &mut self,
+        what: &str,
+        mut condition: impl FnMut(&TestClient) -> bool,
+    ) -> Result<()> {
+        let deadline = Instant::now() + self.timeout;
+
+        loop {
+            self.update()?;
+
+            if condition(self) {
+                return Ok(());
+            }
+
+            if Instant::now() > deadline {
+                return Err(TestClientError::Timeout(what.to_owned()));
+            }
+
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// Wait for a message of type `M` and return it. Messages are returned in the order they
+    /// were received, the other ones are kept for later
+    pub fn expect<M: Replicate<Protocol>>(&mut self) -> Result<M> {
+        let name = std::any::type_name::<M>();
+        self.wait_until(name, |client| client.position::<M>().is_some())?;
+
+        let index = self.position::<M>().unwrap();
+        Ok(self.messages.remove(index).unwrap().cast::<M>().unwrap())
+    }
+
+    fn position<M: ReplicateSafe<Protocol>>(&self) -> Option<usize> {
+        self.messages
+            .iter()
+            .position(|message| message.cast_ref::<M>().is_some())
+    }
+
+    /// Drop the messages of type `M` that weren't expected yet
+    pub fn forget<M: ReplicateSafe<Protocol>>(&mut self) {
+        self.messages
+            .retain(|message| message.cast_ref::<M>().is_none());
+    }
+
+    pub fn send<M: ReplicateSafe<Protocol>>(&mut self, message: &M) {
+        self.client.send_message(Channels::Uno, message);
+    }
+
+    pub fn world(&self) -> &World<Protocol> {
+        &self.world
+    }
+
+    pub fn player_id(&self) -> Option<u64> {
+        self.world
+            .query::<YourPlayerId>()
+            .next()
+            .map(|your_player_id| *your_player_id.id)
+    }
+
+    /// The replicated player of this client
+    pub fn player(&self) -> Option<&Player> {
+        let player_id = self.player_id()?;
+        self.world
+            .query::<Player>()
+            .find(|player| *player.id == player_id)
+    }
+
+    pub fn lobbies(&self) -> Vec<LobbyId> {
+        self.world.query::<Lobby>().map(|lobby| *lobby.id).collect()
+    }
+
+    pub fn hand(&self) -> &[Card] {
+        &self.hand
+    }
+
+    pub fn top_discard(&self) -> Option<Card> {
+        self.top_discard
+    }
+
+    pub fn current_color(&self) -> Option<Color> {
+        self.world
+            .query::<CurrentColor>()
+            .next()
+            .map(|current_color| (*current_color.color).into())
+    }
+
+    /// Whether it is the turn of this client
+    pub fn is_playing(&self) -> bool {
+        self.player().map_or(false, |player| *player.is_playing)
+    }
+
+    /// A card of the hand that can be played on the discard, if any
+    pub fn playable_card(&self) -> Option<Card> {
+        let top_discard = self.top_discard?;
+        let current_color = self.current_color()?;
+
+        self.hand
+            .iter()
+            .copied()
+            .find(|card| card.can_be_played(top_discard, current_color))
+    }
+
+    pub fn set_username(&mut self, username: &str) -> Result<()> {
+        self.send(&Username::new(username.to_owned()));
+        self.wait_until("the new username", |client| {
+            client
+                .player()
+                .map_or(false, |player| *player.username == username)
+        })
+    }
+
+    /// Create a lobby and return its id, without joining it
+    pub fn create_lobby(&mut self) -> Result<LobbyId> {
+        let known_lobbies = self.lobbies();
+        self.send(&CreateLobby::new());
+        self.wait_until("the new lobby", |client| {
+            client
+                .lobbies()
+                .iter()
+                .any(|lobby_id| !known_lobbies.contains(lobby_id))
+        })?;
+
+        Ok(self
+            .lobbies()
+            .into_iter()
+            .find(|lobby_id| !known_lobbies.contains(lobby_id))
+            .unwrap())
+    }
+
+    pub fn join(&mut self, lobby_id: LobbyId) -> Result<()> {
+        self.send(&JoinLobby::new(lobby_id));
+        self.expect::<JoinLobby>().map(|_| ())
+    }
+
+    pub fn leave(&mut self, lobby_id: LobbyId) -> Result<()> {
+        self.send(&LeaveLobby::new(lobby_id));
+        self.expect::<LeaveLobby>().map(|_| ())
+    }
+
+    /// Ask the server to start the game of the current lobby
+    pub fn start_game(&mut self) {
+        self.send(&StartGame::new());
+    }
+
+    /// Play a card and return whether the server accepted it
+    pub fn play(&mut self, card: Card) -> Result<bool> {
+        self.forget::<CardValidation>();
+        self.send(&GameAction::play_card(card));
+
+        let valid = *self.expect::<CardValidation>()?.valid;
+        if valid {
+            if let Some(index) = self.hand.iter().position(|c| *c == card) {
+                self.hand.remove(index);
+            }
+        }
+
+        Ok(valid)
+    }
+
+    /// Draw a card and return it
+    pub fn draw(&mut self) -> Result<Card> {
+        self.forget::<DrawCard>();
+        self.send(&GameAction::draw_card());
+
+        let card = self.expect::<DrawCard>()?;
+        Ok((*card.color, *card.value).into())
+    }
+
+    pub fn choose_color(&mut self, color: Color) {
+        self.send(&GameAction::choose_color(color));
+    }
+
+    pub fn uno(&mut self) {
+        self.send(&GameAction::uno());
+    }
+
+    pub fn counter_uno(&mut self) {
+        self.send(&GameAction::counter_uno());
+    }
+
+    pub fn exit_game(&mut self) {
+        self.send(&GameAction::exit_game());
+        self.hand.clear();
+        self.top_discard = None;
+    }
+}
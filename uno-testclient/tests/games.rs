@@ -0,0 +1,295 @@
+use naia_client::ClientConfig;
+use std::{
+    net::{TcpListener, UdpSocket},
+    thread,
+    time::Duration,
+};
+use uno::{
+    lobby::LobbyId,
+    network::{
+        protocol::{DrawReason, Handshake, Player, PlayerDrew, YourPlayerId},
+        PROTOCOL_VERSION,
+    },
+    rules::INITIAL_CARDS,
+};
+use uno_server::config::Config;
+use uno_testclient::{TestClient, TestClientError, DEFAULT_TIMEOUT};
+
+/// A port the system considers free for both the sessions (TCP) and the data channel (UDP)
+fn free_port() -> u16 {
+    loop {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        if UdpSocket::bind(("127.0.0.1", port)).is_ok() {
+            return port;
+        }
+    }
+}
+
+/// Run a server in the background and return its address once it accepts connections
+fn spawn_server() -> String {
+    spawn_server_with(|_| {})
+}
+
+/// Run a server with some changes to the local configuration
+fn spawn_server_with(configure: impl FnOnce(&mut Config) + Send + 'static) -> String {
+    let port = free_port();
+    thread::spawn(move || {
+        let mut config = Config::local(port);
+        configure(&mut config);
+        uno_server::app(config).run()
+    });
+
+    let address = format!("http://127.0.0.1:{port}");
+    TestClient::connect_when_ready(&address)
+        .unwrap()
+        .disconnect();
+
+    address
+}
+
+/// Connect one client per username and put them all in the same lobby
+fn lobby_with(address: &str, usernames: &[&str]) -> (LobbyId, Vec<TestClient>) {
+    let mut clients: Vec<_> = usernames
+        .iter()
+        .map(|username| {
+            let mut client = TestClient::connect(address).unwrap();
+            client.set_username(username).unwrap();
+            client
+        })
+        .collect();
+
+    let lobby_id = clients[0].create_lobby().unwrap();
+    for client in &mut clients {
+        client.join(lobby_id).unwrap();
+    }
+
+    (lobby_id, clients)
+}
+
+/// Every player id the client was told is its own
+fn your_player_ids(client: &TestClient) -> Vec<u64> {
+    client
+        .world()
+        .query::<YourPlayerId>()
+        .map(|your_player_id| *your_player_id.id)
+        .collect()
+}
+
+fn wait_for_initial_hand(client: &mut TestClient) {
+    client
+        .wait_until("the initial hand", |client| {
+            client.hand().len() == INITIAL_CARDS && client.current_color().is_some()
+        })
+        .unwrap();
+}
+
+#[test]
+fn incompatible_client_is_told_the_server_version() {
+    let address = spawn_server();
+
+    let mut handshake = Handshake::new("0.0.0".to_owned());
+    *handshake.protocol_version = PROTOCOL_VERSION + 1;
+
+    assert!(matches!(
+        TestClient::connect_with(&address, handshake),
+        Err(TestClientError::Incompatible { .. })
+    ));
+}
+
+#[test]
+fn incompatible_client_is_disconnected() {
+    let address = spawn_server();
+
+    let mut handshake = Handshake::new("0.0.0".to_owned());
+    *handshake.protocol_version = PROTOCOL_VERSION + 1;
+
+    // The server sends heartbeats more often than that, only a dropped connection times out
+    let mut config = ClientConfig::default();
+    config.connection.disconnection_timeout_duration = 2 * config.connection.heartbeat_interval;
+    let disconnection_timeout = config.connection.disconnection_timeout_duration;
+
+    let mut client = TestClient::open(&address, handshake, config);
+    assert!(matches!(
+        client.wait_until("the server version", |_| false),
+        Err(TestClientError::Incompatible { .. })
+    ));
+
+    client.timeout = disconnection_timeout + DEFAULT_TIMEOUT;
+    assert!(matches!(
+        client.wait_until("the disconnection", |_| false),
+        Err(TestClientError::Disconnected)
+    ));
+}
+
+#[test]
+fn players_get_their_initial_hand() {
+    let address = spawn_server();
+    let (_, mut clients) = lobby_with(&address, &["Alice", "Bob"]);
+
+    clients[0].start_game();
+    for client in &mut clients {
+        wait_for_initial_hand(client);
+        assert!(client.top_discard().is_some());
+    }
+}
+
+#[test]
+fn simultaneous_start_requests_start_a_single_game() {
+    let address = spawn_server();
+    let (_, mut clients) = lobby_with(&address, &["Alice", "Bob"]);
+
+    for client in &mut clients {
+        client.start_game();
+    }
+
+    for client in &mut clients {
+        wait_for_initial_hand(client);
+    }
+
+    // Make sure no extra card shows up later
+    thread::sleep(Duration::from_millis(200));
+    for client in &mut clients {
+        client.update().unwrap();
+        assert_eq!(client.hand().len(), INITIAL_CARDS);
+    }
+}
+
+#[test]
+fn game_can_be_restarted() {
+    let address = spawn_server();
+    let (_, mut clients) = lobby_with(&address, &["Alice", "Bob"]);
+
+    clients[0].start_game();
+    for client in &mut clients {
+        wait_for_initial_hand(client);
+    }
+
+    for client in &mut clients {
+        client.exit_game();
+    }
+
+    let lobby_id = clients[0].create_lobby().unwrap();
+    for client in &mut clients {
+        client.join(lobby_id).unwrap();
+    }
+
+    clients[1].start_game();
+    for client in &mut clients {
+        wait_for_initial_hand(client);
+    }
+
+    thread::sleep(Duration::from_millis(200));
+    for client in &mut clients {
+        client.update().unwrap();
+        assert_eq!(client.hand().len(), INITIAL_CARDS);
+    }
+}
+
+#[test]
+fn current_player_can_play_or_draw() {
+    let address = spawn_server();
+    let (_, mut clients) = lobby_with(&address, &["Alice", "Bob"]);
+
+    clients[0].start_game();
+    for client in &mut clients {
+        wait_for_initial_hand(client);
+    }
+
+    for client in &mut clients {
+        client
+            .wait_until("a player to be playing", |client| {
+                client
+                    .world()
+                    .query::<Player>()
+                    .any(|player| *player.is_playing)
+            })
+            .unwrap();
+    }
+
+    let current = clients
+        .iter()
+        .position(|client| client.is_playing())
+        .unwrap();
+    let client = &mut clients[current];
+
+    match client.playable_card() {
+        Some(card) => {
+            assert!(client.play(card).unwrap());
+            assert_eq!(client.hand().len(), INITIAL_CARDS - 1);
+        }
+        None => {
+            client.draw().unwrap();
+            assert_eq!(client.hand().len(), INITIAL_CARDS + 1);
+        }
+    }
+}
+
+#[test]
+fn slow_players_draw_a_card_and_lose_their_turn() {
+    let address = spawn_server_with(|config| config.turn_timeout = Some(Duration::from_secs(1)));
+    let (_, mut clients) = lobby_with(&address, &["Alice", "Bob"]);
+
+    clients[0].start_game();
+    for client in &mut clients {
+        wait_for_initial_hand(client);
+    }
+
+    // Nobody plays, the draws after the deal can only come from the timer
+    let drew = loop {
+        let drew = clients[0].expect::<PlayerDrew>().unwrap();
+        if *drew.reason != DrawReason::Deal {
+            break drew;
+        }
+    };
+    assert_eq!(*drew.reason, DrawReason::Timeout);
+    assert_eq!(*drew.count, 1);
+}
+
+#[test]
+fn private_data_in_the_main_room_only_reaches_its_owner() {
+    let address = spawn_server();
+    let mut clients = vec![
+        TestClient::connect(&address).unwrap(),
+        TestClient::connect(&address).unwrap(),
+    ];
+
+    for client in &mut clients {
+        // Once the other player is visible, their private data would have been received too
+        client
+            .wait_until("the other player", |client| {
+                client.world().query::<Player>().count() == 2
+            })
+            .unwrap();
+    }
+
+    for client in &clients {
+        assert_eq!(your_player_ids(client), [client.player_id().unwrap()]);
+    }
+    assert_ne!(clients[0].player_id(), clients[1].player_id());
+}
+
+#[test]
+fn private_data_only_reaches_its_owner() {
+    let address = spawn_server();
+    let (_, mut clients) = lobby_with(&address, &["Alice", "Bob"]);
+
+    clients[0].start_game();
+    for client in &mut clients {
+        wait_for_initial_hand(client);
+
+        // Wait for the deal of both players to be announced, the cards of the other player
+        // would have been received with it
+        for _ in 0..2 {
+            client.expect::<PlayerDrew>().unwrap();
+        }
+        client.update().unwrap();
+    }
+
+    for client in &clients {
+        assert_eq!(your_player_ids(client), [client.player_id().unwrap()]);
+        assert_eq!(client.hand().len(), INITIAL_CARDS);
+    }
+    assert_ne!(clients[0].player_id(), clients[1].player_id());
+}
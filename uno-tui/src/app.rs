@@ -0,0 +1,355 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use naia_client::{Client, Event};
+use uno::{
+    card::Color,
+    chat::sanitize_chat_message,
+    lobby::LobbyId,
+    network::{
+        protocol::{
+            CreateLobby, CurrentColor, GameAction, JoinLobby, LeaveLobby, Lobby, SendChat,
+            StartGame, Username, YourPlayerId,
+        },
+        world::{Entity, World},
+        Channels, Protocol,
+    },
+    texts::{Language, TextId, Texts},
+    username::validate_username,
+    Card,
+};
+
+pub type UnoClient = Client<Protocol, Entity, Channels>;
+
+/// Number of chat lines kept in memory
+const CHAT_HISTORY: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Screen {
+    Connecting,
+    Lobbies,
+    InLobby(LobbyId),
+    Game,
+    EndGame,
+}
+
+/// What the text typed by the user is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextInput {
+    Username,
+    Chat,
+}
+
+pub struct App {
+    pub texts: Texts,
+    pub language: Language,
+    pub screen: Screen,
+    pub player_id: Option<u64>,
+    pub username: String,
+    /// Text being typed, if any
+    pub input: Option<(TextInput, String)>,
+    /// Index of the selected lobby or card
+    pub selected: usize,
+    pub hand: Vec<Card>,
+    pub top_discard: Option<Card>,
+    pub current_color: Color,
+    /// Index of the card sent to the server, waiting for it to be validated
+    pub played_card: Option<usize>,
+    pub choosing_color: bool,
+    pub have_to_draw: bool,
+    pub can_uno: bool,
+    pub can_counter_uno: bool,
+    pub status: Option<String>,
+    pub chat: Vec<String>,
+    pub quit: bool,
+    /// Printed once the terminal is restored
+    pub exit_message: Option<String>,
+}
+
+impl App {
+    pub fn new(texts: Texts, language: Language, username: String) -> App {
+        App {
+            texts,
+            language,
+            screen: Screen::Connecting,
+            player_id: None,
+            username,
+            input: None,
+            selected: 0,
+            hand: vec![],
+            top_discard: None,
+            current_color: Color::Black,
+            played_card: None,
+            choosing_color: false,
+            have_to_draw: false,
+            can_uno: false,
+            can_counter_uno: false,
+            status: None,
+            chat: vec![],
+            quit: false,
+            exit_message: None,
+        }
+    }
+
+    pub fn text(&self, id: TextId) -> String {
+        self.texts.get(id, &self.language)
+    }
+
+    /// Lobbies replicated by the server, sorted by id
+    pub fn lobbies<'w>(&self, world: &'w World<Protocol>) -> Vec<&'w Lobby> {
+        let mut lobbies: Vec<_> = world.query::<Lobby>().collect();
+        lobbies.sort_by_key(|lobby| *lobby.id);
+        lobbies
+    }
+
+    /// Read the replicated components we keep a copy of
+    pub fn sync(&mut self, world: &World<Protocol>) {
+        if let Some(your_player_id) = world.query::<YourPlayerId>().next() {
+            self.player_id = Some(*your_player_id.id);
+        }
+
+        if let Some(current_color) = world.query::<CurrentColor>().next() {
+            self.current_color = (*current_color.color).into();
+        }
+    }
+
+    fn reset_game(&mut self) {
+        self.selected = 0;
+        self.hand.clear();
+        self.top_discard = None;
+        self.current_color = Color::Black;
+        self.played_card = None;
+        self.choosing_color = false;
+        self.have_to_draw = false;
+        self.can_uno = false;
+        self.can_counter_uno = false;
+    }
+
+    pub fn handle_event(
+        &mut self,
+        event: Event<Protocol, Entity, Channels>,
+        client: &mut UnoClient,
+    ) {
+        match event {
+            Event::Connection(_) => {
+                self.screen = Screen::Lobbies;
+                self.send_username(client);
+            }
+            Event::Rejection(_) => {
+                self.exit_message = Some(self.text(TextId::ConnectionRejected));
+                self.quit = true;
+            }
+            Event::Disconnection(address) => {
+                self.exit_message = Some(self.texts.format(
+                    TextId::DisconnectedFrom,
+                    &self.language,
+                    &[("address", &address.to_string())],
+                ));
+                self.quit = true;
+            }
+            Event::Message(_, message) => self.handle_message(message),
+            _ => {}
+        }
+    }
+
+    fn handle_message(&mut self, message: Protocol) {
+        match message {
+            Protocol::JoinLobby(lobby) => self.screen = Screen::InLobby(*lobby.id),
+            Protocol::LeaveLobby(_) => self.screen = Screen::Lobbies,
+            Protocol::StartGame(_) => {
+                self.reset_game();
+                self.screen = Screen::Game;
+            }
+            Protocol::GameEnd(_) => self.screen = Screen::EndGame,
+            Protocol::DrawCard(card) => {
+                self.hand.push((*card.color, *card.value).into());
+                self.have_to_draw = false;
+            }
+            Protocol::CardPlayed(card) => {
+                self.top_discard = Some((*card.color, *card.value).into())
+            }
+            Protocol::CardValidation(validation) => {
+                if let Some(index) = self.played_card.take() {
+                    if *validation.valid && index < self.hand.len() {
+                        let card = self.hand.remove(index);
+                        self.top_discard = Some(card);
+                        self.choosing_color = card.color == Color::Black;
+                        self.selected = self.selected.min(self.hand.len().saturating_sub(1));
+                    }
+                }
+            }
+            Protocol::GameSnapshot(snapshot) => {
+                self.hand = snapshot.hand();
+                self.top_discard = Some(snapshot.top_discard());
+                self.current_color = snapshot.current_color();
+                self.selected = self.selected.min(self.hand.len().saturating_sub(1));
+            }
+            Protocol::HaveToDrawCard(_) => self.have_to_draw = true,
+            Protocol::Uno(_) => self.can_uno = true,
+            Protocol::CounterUno(_) => self.can_counter_uno = true,
+            Protocol::StopUno(_) => {
+                self.can_uno = false;
+                self.can_counter_uno = false;
+            }
+            Protocol::ChatMessage(message) => {
+                self.chat
+                    .push(format!("{}: {}", *message.username, *message.text));
+                if self.chat.len() > CHAT_HISTORY {
+                    self.chat.remove(0);
+                }
+            }
+            Protocol::IncompatibleVersion(incompatible) => {
+                self.exit_message = Some(self.texts.format(
+                    TextId::IncompatibleVersion,
+                    &self.language,
+                    &[("version", incompatible.server_version.as_str())],
+                ));
+                self.quit = true;
+            }
+            Protocol::UsernameRejected(rejected) => {
+                self.status = Some(self.text(rejected.error.text_id()))
+            }
+            Protocol::Error(error) => self.status = Some((*error.error).clone()),
+            _ => {}
+        }
+    }
+
+    fn send_username(&mut self, client: &mut UnoClient) {
+        if self.username.is_empty() {
+            return;
+        }
+
+        match validate_username(&self.username, &[]) {
+            Ok(username) => client.send_message(Channels::Uno, &Username::new(username)),
+            Err(error) => self.status = Some(self.text(error.text_id())),
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent, client: &mut UnoClient, world: &World<Protocol>) {
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.quit = true;
+            return;
+        }
+
+        if let Some((input, mut text)) = self.input.take() {
+            match key.code {
+                KeyCode::Char(c) => {
+                    text.push(c);
+                    self.input = Some((input, text));
+                }
+                KeyCode::Backspace => {
+                    text.pop();
+                    self.input = Some((input, text));
+                }
+                KeyCode::Enter => match input {
+                    TextInput::Username => {
+                        self.username = text;
+                        self.send_username(client);
+                    }
+                    TextInput::Chat => {
+                        if let Some(text) = sanitize_chat_message(&text) {
+                            client.send_message(Channels::Chat, &SendChat::new(text));
+                        }
+                    }
+                },
+                KeyCode::Esc => {}
+                _ => self.input = Some((input, text)),
+            }
+            return;
+        }
+
+        self.status = None;
+
+        match self.screen {
+            Screen::Connecting => {
+                if let KeyCode::Char('q') | KeyCode::Esc = key.code {
+                    self.quit = true;
+                }
+            }
+            Screen::Lobbies => self.lobbies_key(key.code, client, world),
+            Screen::InLobby(lobby_id) => match key.code {
+                KeyCode::Char('s') => client.send_message(Channels::Uno, &StartGame::new()),
+                KeyCode::Char('l') | KeyCode::Esc => {
+                    client.send_message(Channels::Uno, &LeaveLobby::new(lobby_id))
+                }
+                KeyCode::Char('t') => self.input = Some((TextInput::Chat, String::new())),
+                _ => {}
+            },
+            Screen::Game => self.game_key(key.code, client),
+            Screen::EndGame => {
+                if let KeyCode::Enter | KeyCode::Esc = key.code {
+                    self.exit_game(client);
+                }
+            }
+        }
+    }
+
+    fn lobbies_key(&mut self, code: KeyCode, client: &mut UnoClient, world: &World<Protocol>) {
+        let lobbies = self.lobbies(world);
+
+        match code {
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => {
+                self.selected = (self.selected + 1).min(lobbies.len().saturating_sub(1))
+            }
+            KeyCode::Enter => {
+                if let Err(error) = validate_username(&self.username, &[]) {
+                    self.status = Some(self.text(error.text_id()));
+                } else if let Some(lobby) = lobbies.get(self.selected) {
+                    client.send_message(Channels::Uno, &JoinLobby::new(*lobby.id));
+                }
+            }
+            KeyCode::Char('c') => client.send_message(Channels::Uno, &CreateLobby::new()),
+            KeyCode::Char('n') => self.input = Some((TextInput::Username, self.username.clone())),
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            _ => {}
+        }
+    }
+
+    fn game_key(&mut self, code: KeyCode, client: &mut UnoClient) {
+        if self.choosing_color {
+            let color = match code {
+                KeyCode::Char('y') => Color::Yellow,
+                KeyCode::Char('r') => Color::Red,
+                KeyCode::Char('b') => Color::Blue,
+                KeyCode::Char('g') => Color::Green,
+                _ => return,
+            };
+            client.send_message(Channels::Uno, &GameAction::choose_color(color));
+            self.choosing_color = false;
+            return;
+        }
+
+        match code {
+            KeyCode::Left => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Right => {
+                self.selected = (self.selected + 1).min(self.hand.len().saturating_sub(1))
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                if let (Some(card), None) = (self.hand.get(self.selected), self.played_card) {
+                    client.send_message(Channels::Uno, &GameAction::play_card(*card));
+                    self.played_card = Some(self.selected);
+                }
+            }
+            KeyCode::Char('d') if self.have_to_draw => {
+                client.send_message(Channels::Uno, &GameAction::draw_card());
+                self.have_to_draw = false;
+            }
+            KeyCode::Char('u') if self.can_uno => {
+                client.send_message(Channels::Uno, &GameAction::uno());
+                self.can_uno = false;
+            }
+            KeyCode::Char('x') if self.can_counter_uno => {
+                client.send_message(Channels::Uno, &GameAction::counter_uno());
+                self.can_counter_uno = false;
+            }
+            KeyCode::Char('t') => self.input = Some((TextInput::Chat, String::new())),
+            KeyCode::Char('q') | KeyCode::Esc => self.exit_game(client),
+            _ => {}
+        }
+    }
+
+    fn exit_game(&mut self, client: &mut UnoClient) {
+        client.send_message(Channels::Uno, &GameAction::exit_game());
+        self.reset_game();
+        self.screen = Screen::Lobbies;
+    }
+}
@@ -0,0 +1,99 @@
+mod app;
+mod ui;
+
+use app::{App, UnoClient};
+use crossterm::{
+    cursor, event, execute,
+    terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use naia_client::ClientConfig;
+use std::{
+    env,
+    io::{self, Stdout},
+    thread,
+    time::Duration,
+};
+use uno::{
+    network::{protocol::Handshake, shared_config, world::World},
+    texts::{Language, Texts},
+};
+
+const DEFAULT_SERVER_ADDRESS: &str = "http://127.0.0.1:3478";
+const FRAME_DURATION: Duration = Duration::from_millis(16);
+
+/// Puts the terminal back in its normal state when dropped, even if the client panics
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn new(stdout: &mut Stdout) -> io::Result<TerminalGuard> {
+        terminal::enable_raw_mode()?;
+        execute!(stdout, EnterAlternateScreen, cursor::Hide)?;
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), LeaveAlternateScreen, cursor::Show);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: uno-tui [--server <address>] [--username <username>]");
+    std::process::exit(1);
+}
+
+fn main() -> io::Result<()> {
+    let mut server_address = DEFAULT_SERVER_ADDRESS.to_owned();
+    let mut username = String::new();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--server" => server_address = args.next().unwrap_or_else(|| usage()),
+            "--username" => username = args.next().unwrap_or_else(|| usage()),
+            _ => usage(),
+        }
+    }
+
+    let language =
+        env::var("LANG").map_or_else(|_| Language::english(), |lang| Language::from_locale(&lang));
+
+    let mut client = UnoClient::new(&ClientConfig::default(), &shared_config());
+    client.auth(Handshake::new(env!("CARGO_PKG_VERSION").to_owned()));
+    client.connect(&server_address);
+
+    let mut world = World::default();
+    let mut app = App::new(Texts::builtin(), language, username);
+
+    let mut stdout = io::stdout();
+    let guard = TerminalGuard::new(&mut stdout)?;
+
+    while !app.quit {
+        for event in client.receive(world.proxy_mut()) {
+            match event {
+                Ok(event) => app.handle_event(event, &mut client),
+                Err(err) => app.status = Some(format!("{err:?}")),
+            }
+        }
+        app.sync(&world);
+
+        while event::poll(Duration::ZERO)? {
+            if let event::Event::Key(key) = event::read()? {
+                app.handle_key(key, &mut client, &world);
+            }
+        }
+
+        ui::draw(&mut stdout, &app, &world)?;
+        thread::sleep(FRAME_DURATION);
+    }
+
+    drop(guard);
+
+    if let Some(message) = app.exit_message {
+        println!("{message}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,311 @@
+use crate::app::{App, Screen, TextInput};
+use crossterm::{
+    cursor::MoveTo,
+    queue,
+    style::{Attribute, Color as TermColor, Print, StyledContent, Stylize},
+    terminal::{Clear, ClearType},
+};
+use std::{
+    fmt::Display,
+    io::{self, Write},
+};
+use uno::{
+    card::{Color, Value},
+    lobby::LobbyId,
+    network::{
+        protocol::{Player, TableState},
+        world::World,
+        Protocol,
+    },
+    texts::TextId,
+    Card,
+};
+
+/// Number of chat lines shown under the game
+const CHAT_LINES: usize = 5;
+
+/// Writes the screen line by line, clearing what was left from the previous frame
+struct Frame<'a, W: Write> {
+    out: &'a mut W,
+    row: u16,
+}
+
+impl<'a, W: Write> Frame<'a, W> {
+    fn line(&mut self, text: impl Display) -> io::Result<()> {
+        self.spans([text])
+    }
+
+    fn spans<D: Display>(&mut self, spans: impl IntoIterator<Item = D>) -> io::Result<()> {
+        queue!(self.out, MoveTo(0, self.row))?;
+        for span in spans {
+            queue!(self.out, Print(span))?;
+        }
+        queue!(self.out, Clear(ClearType::UntilNewLine))?;
+        self.row += 1;
+        Ok(())
+    }
+
+    fn skip(&mut self) -> io::Result<()> {
+        self.line("")
+    }
+
+    fn finish(self) -> io::Result<()> {
+        queue!(
+            self.out,
+            MoveTo(0, self.row),
+            Clear(ClearType::FromCursorDown)
+        )?;
+        self.out.flush()
+    }
+}
+
+fn term_color(color: Color) -> TermColor {
+    match color {
+        Color::Yellow => TermColor::Yellow,
+        Color::Red => TermColor::Red,
+        Color::Blue => TermColor::Blue,
+        Color::Green => TermColor::Green,
+        Color::Black => TermColor::DarkGrey,
+    }
+}
+
+fn card_label(card: Card) -> String {
+    match card.value {
+        Value::DrawTwo => "+2".to_owned(),
+        Value::Skip => "Ø".to_owned(),
+        Value::Reverse => "⇄".to_owned(),
+        Value::Wild => "W".to_owned(),
+        Value::WildFour => "W+4".to_owned(),
+        Value::Back => "?".to_owned(),
+        // Zero is stored as 10
+        value => (value as u8 % 10).to_string(),
+    }
+}
+
+fn styled_card(card: Card, selected: bool) -> StyledContent<String> {
+    let label = format!(" {} ", card_label(card))
+        .with(TermColor::White)
+        .on(term_color(card.color));
+
+    if selected {
+        label
+            .attribute(Attribute::Reverse)
+            .attribute(Attribute::Bold)
+    } else {
+        label
+    }
+}
+
+fn hints(keys: &[(&str, String)]) -> String {
+    keys.iter()
+        .map(|(key, action)| format!("[{key}] {action}"))
+        .collect::<Vec<_>>()
+        .join("  ")
+}
+
+pub fn draw(out: &mut impl Write, app: &App, world: &World<Protocol>) -> io::Result<()> {
+    let mut frame = Frame { out, row: 0 };
+
+    frame.line(app.text(TextId::UnoTitle).bold())?;
+    frame.skip()?;
+
+    match app.screen {
+        Screen::Connecting => frame.line("…")?,
+        Screen::Lobbies => draw_lobbies(&mut frame, app, world)?,
+        Screen::InLobby(lobby_id) => draw_lobby(&mut frame, app, world, lobby_id)?,
+        Screen::Game => draw_game(&mut frame, app, world)?,
+        Screen::EndGame => draw_end_game(&mut frame, app, world)?,
+    }
+
+    if let Screen::InLobby(_) | Screen::Game = app.screen {
+        frame.skip()?;
+        frame.line(app.text(TextId::Chat).underlined())?;
+        for message in app.chat.iter().rev().take(CHAT_LINES).rev() {
+            frame.line(message)?;
+        }
+    }
+
+    if let Some((input, text)) = &app.input {
+        frame.skip()?;
+        let label = match input {
+            TextInput::Username => app.text(TextId::Username),
+            TextInput::Chat => app.text(TextId::Chat),
+        };
+        frame.line(format!("{label} > {text}_"))?;
+    }
+
+    if let Some(status) = &app.status {
+        frame.skip()?;
+        frame.line(status.as_str().red())?;
+    }
+
+    frame.finish()
+}
+
+fn draw_lobbies<W: Write>(
+    frame: &mut Frame<W>,
+    app: &App,
+    world: &World<Protocol>,
+) -> io::Result<()> {
+    frame.line(app.text(TextId::LobbiesTitle).underlined())?;
+    frame.line(format!("{}: {}", app.text(TextId::Username), app.username))?;
+    frame.skip()?;
+
+    for (i, lobby) in app.lobbies(world).iter().enumerate() {
+        let text = format!(
+            "{} #{}  {}/10",
+            app.text(TextId::Lobby),
+            *lobby.id,
+            *lobby.number_of_players
+        );
+
+        if i == app.selected {
+            frame.line(format!("> {text}").bold())?;
+        } else {
+            frame.line(format!("  {text}"))?;
+        }
+    }
+
+    frame.skip()?;
+    frame.line(hints(&[
+        ("↑↓ Enter", app.text(TextId::JoinLobby)),
+        ("c", app.text(TextId::CreateLobby)),
+        ("n", app.text(TextId::Username)),
+        ("q", app.text(TextId::Quit)),
+    ]))
+}
+
+fn draw_lobby<W: Write>(
+    frame: &mut Frame<W>,
+    app: &App,
+    world: &World<Protocol>,
+    lobby_id: LobbyId,
+) -> io::Result<()> {
+    frame.line(format!("{} #{lobby_id}", app.text(TextId::Lobby)).underlined())?;
+
+    for player in world.query::<Player>() {
+        if *player.in_lobby == Some(lobby_id) {
+            frame.line(player_name(app, player))?;
+        }
+    }
+
+    frame.skip()?;
+    frame.line(hints(&[
+        ("s", app.text(TextId::StartGame)),
+        ("l", app.text(TextId::LeaveLobby)),
+        ("t", app.text(TextId::Chat)),
+    ]))
+}
+
+fn player_name(app: &App, player: &Player) -> StyledContent<String> {
+    let name = (*player.username).clone();
+
+    if Some(*player.id) == app.player_id {
+        name.bold().underlined()
+    } else {
+        name.stylize()
+    }
+}
+
+fn sorted_players<'w>(world: &'w World<Protocol>) -> Vec<&'w Player> {
+    let mut players: Vec<_> = world.query::<Player>().collect();
+    players.sort_by_key(|player| *player.id);
+    players
+}
+
+fn draw_game<W: Write>(frame: &mut Frame<W>, app: &App, world: &World<Protocol>) -> io::Result<()> {
+    for player in sorted_players(world) {
+        let marker = if *player.is_playing { "▶ " } else { "  " };
+        frame.spans([
+            marker.to_owned().stylize(),
+            player_name(app, player),
+            format!(" ({})", *player.hand_size).stylize(),
+        ])?;
+    }
+
+    frame.skip()?;
+
+    if let Some(table_state) = world.query::<TableState>().next() {
+        let direction = if *table_state.reverse_turn {
+            "⬅"
+        } else {
+            "➡"
+        };
+        frame.line(format!(
+            "{direction}  {}: {}",
+            app.text(TextId::DrawPile),
+            *table_state.draw_pile_size
+        ))?;
+    }
+
+    if let Some(top_discard) = app.top_discard {
+        frame.spans([
+            "   ".to_owned().on(term_color(app.current_color)),
+            " ".to_owned().stylize(),
+            styled_card(top_discard, false),
+        ])?;
+    }
+
+    frame.skip()?;
+    frame.spans(app.hand.iter().enumerate().flat_map(|(i, card)| {
+        [
+            styled_card(*card, i == app.selected),
+            " ".to_owned().stylize(),
+        ]
+    }))?;
+    frame.skip()?;
+
+    if app.choosing_color {
+        frame.spans([
+            format!("{}: ", app.text(TextId::ChooseColor)).stylize(),
+            "[y]".to_owned().yellow(),
+            " ".to_owned().stylize(),
+            "[r]".to_owned().red(),
+            " ".to_owned().stylize(),
+            "[b]".to_owned().blue(),
+            " ".to_owned().stylize(),
+            "[g]".to_owned().green(),
+        ])?;
+    }
+
+    let mut keys = vec![("←→ Enter", app.text(TextId::Play))];
+    if app.have_to_draw {
+        keys.push(("d", app.text(TextId::DrawCard)));
+    }
+    if app.can_uno {
+        keys.push(("u", app.text(TextId::Uno)));
+    }
+    if app.can_counter_uno {
+        keys.push(("x", app.text(TextId::CounterUno)));
+    }
+    keys.push(("t", app.text(TextId::Chat)));
+    keys.push(("q", app.text(TextId::BackToMenu)));
+
+    frame.line(hints(&keys))
+}
+
+fn draw_end_game<W: Write>(
+    frame: &mut Frame<W>,
+    app: &App,
+    world: &World<Protocol>,
+) -> io::Result<()> {
+    frame.line(app.text(TextId::EndGameTitle).underlined())?;
+    frame.line(format!(
+        "{:<20} {:>8} {:>8}",
+        app.text(TextId::Username),
+        app.text(TextId::Score),
+        app.text(TextId::RemainingCards)
+    ))?;
+
+    let mut players = sorted_players(world);
+    players.sort_by_key(|player| *player.score);
+    for player in players {
+        frame.line(format!(
+            "{:<20} {:>8} {:>8}",
+            *player.username, *player.score, *player.hand_size
+        ))?;
+    }
+
+    frame.skip()?;
+    frame.line(hints(&[("Enter", app.text(TextId::BackToMenu))]))
+}
@@ -0,0 +1,31 @@
+use naia_shared::{derive_serde, serde};
+
+/// The maximum amount of characters in a chat message, longer messages are truncated
+pub const MAX_CHAT_MESSAGE_LENGTH: usize = 200;
+
+/// Messages that can be sent in one click
+pub const EMOTES: &[&str] = &["Uno!", "GG", "👍", "😂", "😮", "😡"];
+
+// Where a chat message was sent
+#[derive_serde]
+pub enum ChatScope {
+    Lobby,
+    Game,
+}
+
+/// Trim a chat message, strip the control characters and cut it to the maximum length.
+/// Returns `None` if there is nothing left to send
+pub fn sanitize_chat_message(text: &str) -> Option<String> {
+    let text: String = text
+        .trim()
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_CHAT_MESSAGE_LENGTH)
+        .collect();
+
+    if text.is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
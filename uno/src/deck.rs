@@ -81,7 +81,7 @@ impl Deck {
     }
 
     /// Peek at the card on top of the deck
-    pub fn top(&mut self) -> Option<&Card> {
+    pub fn top(&self) -> Option<&Card> {
         self.cards.get(0)
     }
 }
@@ -2,11 +2,19 @@
 pub enum UnoError {
     Disconnected,
     MessageNotBinary,
+    IncompatibleProtocol { server: u32, client: u32 },
 }
 
 impl std::fmt::Display for UnoError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        match self {
+            UnoError::Disconnected => write!(f, "disconnected from the server"),
+            UnoError::MessageNotBinary => write!(f, "received a message that isn't binary"),
+            UnoError::IncompatibleProtocol { server, client } => write!(
+                f,
+                "the client uses protocol version {client} but the server uses version {server}"
+            ),
+        }
     }
 }
 
@@ -1,10 +1,13 @@
 pub mod card;
+pub mod chat;
 pub mod deck;
 pub mod error;
 pub mod lobby;
 pub mod network;
 pub mod player;
+pub mod rules;
 pub mod texts;
+pub mod username;
 
 pub use card::Card;
 pub use deck::Deck;
@@ -17,4 +20,222 @@ mod tests {
     fn full_deck_size() {
         assert_eq!(crate::deck::Deck::full().size(), 108);
     }
+
+    #[test]
+    fn protocol_version_check() {
+        use crate::network::{check_protocol_version, PROTOCOL_VERSION};
+
+        assert!(check_protocol_version(PROTOCOL_VERSION).is_ok());
+        assert!(check_protocol_version(PROTOCOL_VERSION + 1).is_err());
+    }
+
+    #[test]
+    fn username_validation() {
+        use crate::username::{validate_username, UsernameError};
+
+        assert_eq!(
+            validate_username("  Morgan ", &[]).ok(),
+            Some("Morgan".to_owned())
+        );
+        assert!(matches!(
+            validate_username("   ", &[]),
+            Err(UsernameError::Empty)
+        ));
+        assert!(matches!(
+            validate_username(&"a".repeat(21), &[]),
+            Err(UsernameError::TooLong)
+        ));
+        assert!(matches!(
+            validate_username("tab\tname", &[]),
+            Err(UsernameError::InvalidCharacters)
+        ));
+        assert!(matches!(
+            validate_username("xXBadWordXx", &["badword".to_owned()]),
+            Err(UsernameError::Blocked)
+        ));
+    }
+
+    #[test]
+    fn chat_message_sanitizing() {
+        use crate::chat::{sanitize_chat_message, MAX_CHAT_MESSAGE_LENGTH};
+
+        assert_eq!(sanitize_chat_message("  GG \n"), Some("GG".to_owned()));
+        assert_eq!(sanitize_chat_message(" \t "), None);
+        assert_eq!(
+            sanitize_chat_message(&"a".repeat(300)).map(|text| text.len()),
+            Some(MAX_CHAT_MESSAGE_LENGTH)
+        );
+    }
+
+    #[test]
+    fn rules_deal_and_validate() {
+        use crate::{
+            card::{Card, Color, Value},
+            network::{
+                protocol::{DrawReason, GameAction},
+                Protocol,
+            },
+            rules::{GameRules, INITIAL_CARDS},
+        };
+
+        let mut game = GameRules::new(vec!["Alice".to_owned(), "Bob".to_owned()]);
+        for seat in &game.seats {
+            assert_eq!(seat.player.hand.len(), INITIAL_CARDS);
+        }
+
+        let messages = game.messages();
+        let dealt = messages
+            .iter()
+            .filter(|(_, message)| matches!(message, Protocol::DrawCard(_)))
+            .count();
+        assert_eq!(dealt, 2 * INITIAL_CARDS);
+
+        // Everyone is told how many cards each player got, not which ones
+        let announced = messages
+            .iter()
+            .filter(|(_, message)| match message {
+                Protocol::PlayerDrew(player_drew) => {
+                    *player_drew.count as usize == INITIAL_CARDS
+                        && *player_drew.reason == DrawReason::Deal
+                }
+                _ => false,
+            })
+            .count();
+        assert_eq!(announced, 2 * 2);
+
+        // Only the current player can play, and only cards from their hand
+        let waiting_seat = 1 - game.current_seat();
+        let card = game.seats[waiting_seat].player.hand[0];
+        game.apply(waiting_seat, &GameAction::play_card(card));
+        game.apply(
+            game.current_seat(),
+            &GameAction::play_card(Card::new(Value::Back, Color::Black)),
+        );
+
+        let validations: Vec<_> = game
+            .messages()
+            .into_iter()
+            .filter_map(|(_, message)| match message {
+                Protocol::CardValidation(validation) => Some(*validation.valid),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(validations, vec![false, false]);
+    }
+
+    #[test]
+    fn leaving_passes_the_turn() {
+        use crate::rules::GameRules;
+
+        let usernames = ["Alice", "Bob", "Carol"].map(str::to_owned).to_vec();
+        let mut game = GameRules::new(usernames);
+        let current = game.current_seat();
+        let next_id = game.seats[(current + 1) % 3].id;
+
+        let left = game.leave(current);
+        assert_eq!(game.seats.len(), 2);
+        assert!(game.seats.iter().all(|seat| seat.id != left.id));
+        assert_eq!(game.seats[game.current_seat()].id, next_id);
+        assert!(game.seats[game.current_seat()].player.is_playing);
+    }
+
+    #[test]
+    fn timeout_draws_a_card_and_passes_the_turn() {
+        use crate::{
+            network::{
+                protocol::{DrawReason, GameLogKind},
+                Protocol,
+            },
+            player::PlayerState,
+            rules::{GameRules, INITIAL_CARDS},
+        };
+
+        let usernames = ["Alice", "Bob", "Carol"].map(str::to_owned).to_vec();
+        let mut game = GameRules::new(usernames);
+        game.messages();
+
+        let seat = game.current_seat();
+        assert!(game.time_out());
+        assert_eq!(game.seats[seat].player.hand.len(), INITIAL_CARDS + 1);
+        assert_ne!(game.current_seat(), seat);
+        assert!(game.messages().iter().any(|(_, message)| match message {
+            Protocol::PlayerDrew(player_drew) => *player_drew.reason == DrawReason::Timeout,
+            _ => false,
+        }));
+
+        // A color is chosen for a player who played a wild card and let the time run out
+        let seat = game.current_seat();
+        game.seats[seat].player.state = PlayerState::ChoosingColorWild;
+        assert!(game.time_out());
+        assert_ne!(game.current_seat(), seat);
+        assert!(game.messages().iter().any(|(_, message)| match message {
+            Protocol::GameLog(log) => matches!(*log.kind, GameLogKind::ChoseColor { .. }),
+            _ => false,
+        }));
+    }
+
+    #[test]
+    fn round_results_and_next_round() {
+        use crate::{
+            card::{Card, Color, Value},
+            network::protocol::RoundResult,
+            rules::{GameRules, INITIAL_CARDS},
+        };
+
+        let mut game = GameRules::new(vec!["Alice".to_owned(), "Bob".to_owned()]);
+        let player = &mut game.seats[0].player;
+        player.hand = vec![
+            Card::new(Value::Wild, Color::Black),
+            Card::new(Value::Five, Color::Red),
+        ];
+        player.round.cards_played = 3;
+        let round_score = player.compute_score();
+        player.score += round_score + 10;
+
+        let result = RoundResult::new(1, player, round_score);
+        assert_eq!(result.round_score, 55);
+        assert_eq!(result.total_score, 65);
+        assert_eq!(result.cards_played, 3);
+        assert_eq!(result.hand(), player.hand);
+
+        // The scores are kept, the hands and the stats aren't
+        let next_round = game.next_round();
+        assert_eq!(next_round.seats[0].player.score, 65);
+        for seat in &next_round.seats {
+            assert_eq!(seat.player.hand.len(), INITIAL_CARDS);
+            assert_eq!(seat.player.round.cards_played, 0);
+        }
+    }
+
+    #[test]
+    fn texts_fallback_and_placeholders() {
+        use crate::texts::{Language, TextId, Texts};
+
+        let mut texts = Texts::builtin();
+        texts
+            .add_locale(
+                Language("xx".to_owned()),
+                r#"(name: "Test", texts: { Ok: "Okay {n}" })"#,
+            )
+            .unwrap();
+        let test_language = Language("xx".to_owned());
+
+        assert_eq!(texts.get(TextId::Ok, &test_language), "Okay {n}");
+        assert_eq!(texts.get(TextId::Cancel, &test_language), "Cancel");
+        assert_eq!(
+            texts.format(TextId::Ok, &test_language, &[("n", "2")]),
+            "Okay 2"
+        );
+        assert_eq!(Language::from_locale("fr_FR.UTF-8").0, "fr");
+
+        // Texts of other versions of the game are skipped, not the whole locale
+        let unknown_ids = texts
+            .add_locale(
+                Language("yy".to_owned()),
+                r#"(name: "Other", texts: { Ok: "Fine", Removed: "Gone" })"#,
+            )
+            .unwrap();
+        assert_eq!(unknown_ids, vec!["Removed".to_owned()]);
+        assert_eq!(texts.get(TextId::Ok, &Language("yy".to_owned())), "Fine");
+    }
 }
@@ -5,6 +5,7 @@ pub enum Channels {
     // Lobby,
     // Game,
     Uno,
+    Chat,
 }
 
 pub const CHANNEL_CONFIG: &[Channel<Channels>] = &[
@@ -25,4 +26,9 @@ pub const CHANNEL_CONFIG: &[Channel<Channels>] = &[
         direction: ChannelDirection::Bidirectional,
         mode: ChannelMode::OrderedReliable(ReliableSettings::default()),
     },
+    Channel {
+        index: Channels::Chat,
+        direction: ChannelDirection::Bidirectional,
+        mode: ChannelMode::OrderedReliable(ReliableSettings::default()),
+    },
 ];
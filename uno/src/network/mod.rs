@@ -1,7 +1,12 @@
 pub mod channels;
+pub mod property;
 pub mod protocol;
 pub mod shared_config;
+pub mod version;
+pub mod world;
 
 pub use channels::Channels;
+pub use property::set_if_changed;
 pub use protocol::Protocol;
 pub use shared_config::shared_config;
+pub use version::{check_protocol_version, PROTOCOL_VERSION};
@@ -0,0 +1,9 @@
+use naia_shared::{serde::Serde, Property};
+
+/// Assign a replicated property only when its value is different. Writing to a property marks it
+/// as changed, so assigning it on every tick would replicate it on every tick
+pub fn set_if_changed<T: Serde>(property: &mut Property<T>, value: T) {
+    if **property != value {
+        **property = value;
+    }
+}
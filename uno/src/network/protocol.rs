@@ -1,53 +1,69 @@
 mod card_played;
 mod card_validation;
-mod choose_color;
-mod color_chosen;
+mod chat_message;
 mod counter_uno;
 mod create_lobby;
 mod current_color;
 mod draw_card;
 mod error;
+mod game_action;
 mod game_end;
-mod game_exit;
+mod game_log;
+mod game_snapshot;
+mod handshake;
 mod have_to_draw_card;
+mod incompatible_version;
 mod join_lobby;
 mod leave_lobby;
 mod lobby;
-mod play_card;
 mod player;
+mod player_drew;
+mod send_chat;
 mod start_game;
 mod stop_uno;
+mod table_state;
 mod uno;
 mod username;
+mod username_rejected;
 mod your_player_id;
 
 pub use card_played::CardPlayed;
 pub use card_validation::CardValidation;
-pub use choose_color::ChooseColor;
-pub use color_chosen::ColorChosen;
+pub use chat_message::ChatMessage;
 pub use counter_uno::CounterUno;
 pub use create_lobby::CreateLobby;
 pub use current_color::CurrentColor;
 pub use draw_card::DrawCard;
 pub use error::Error;
-pub use game_end::GameEnd;
-pub use game_exit::GameExit;
+pub use game_action::{GameAction, GameActionKind};
+pub use game_end::{GameEnd, RoundResult};
+pub use game_log::{GameLog, GameLogKind};
+pub use game_snapshot::{GameSnapshot, NetworkCard, SnapshotPlayer};
+pub use handshake::Handshake;
 pub use have_to_draw_card::HaveToDrawCard;
+pub use incompatible_version::IncompatibleVersion;
 pub use join_lobby::JoinLobby;
 pub use leave_lobby::LeaveLobby;
 pub use lobby::Lobby;
-pub use play_card::PlayCard;
 pub use player::Player;
+pub use player_drew::{DrawReason, PlayerDrew};
+pub use send_chat::SendChat;
 pub use start_game::StartGame;
 pub use stop_uno::StopUno;
+pub use table_state::TableState;
 pub use uno::Uno;
 pub use username::Username;
+pub use username_rejected::UsernameRejected;
 pub use your_player_id::YourPlayerId;
 
 use naia_shared::Protocolize;
 
 #[derive(Protocolize)]
 pub enum Protocol {
+    // The first two messages must never move, so that clients of any version can be told that
+    // they need an update
+    Handshake(Handshake),
+    IncompatibleVersion(IncompatibleVersion),
     Player(Player),
     YourPlayerId(YourPlayerId),
     Lobby(Lobby),
@@ -55,21 +71,26 @@ pub enum Protocol {
     JoinLobby(JoinLobby),
     LeaveLobby(LeaveLobby),
     Username(Username),
+    UsernameRejected(UsernameRejected),
     StartGame(StartGame),
     GameEnd(GameEnd),
-    GameExit(GameExit),
     // In game commmands
-    PlayCard(PlayCard),
+    GameAction(GameAction),
     CardPlayed(CardPlayed),
     CardValidation(CardValidation),
     DrawCard(DrawCard),
-    ChooseColor(ChooseColor),
-    ColorChosen(ColorChosen),
+    PlayerDrew(PlayerDrew),
     CurrentColor(CurrentColor),
+    TableState(TableState),
     Uno(Uno),
     StopUno(StopUno),
     CounterUno(CounterUno),
     HaveToDrawCard(HaveToDrawCard),
+    GameSnapshot(GameSnapshot),
+    GameLog(GameLog),
+    // Chat
+    SendChat(SendChat),
+    ChatMessage(ChatMessage),
     // Other
     Error(Error),
 }
@@ -0,0 +1,18 @@
+use crate::chat::ChatScope;
+use bevy_ecs::prelude::Component;
+use naia_shared::{Property, Replicate};
+
+#[derive(Component, Replicate)]
+#[protocol_path = "crate::network::protocol::Protocol"]
+pub struct ChatMessage {
+    pub sender_id: Property<u64>,
+    pub username: Property<String>,
+    pub text: Property<String>,
+    pub scope: Property<ChatScope>,
+}
+
+impl ChatMessage {
+    pub fn new(sender_id: u64, username: String, text: String, scope: ChatScope) -> Self {
+        ChatMessage::new_complete(sender_id, username, text, scope)
+    }
+}
@@ -0,0 +1,74 @@
+use crate::card::{Card, Color};
+use bevy_ecs::prelude::Component;
+use naia_shared::{derive_serde, serde, Property, Replicate};
+
+// `PlayAgain` asks to go back to the lobby of the game once it is over, with the same players
+#[derive_serde]
+pub enum GameActionKind {
+    PlayCard,
+    DrawCard,
+    ChooseColor,
+    Uno,
+    CounterUno,
+    ExitGame,
+    RequestSnapshot,
+    PlayAgain,
+}
+
+/// Action sent by a client during a game. The card fields are only meaningful for the actions
+/// that need them (the played card, or the color chosen after a wild card)
+#[derive(Component, Replicate)]
+#[protocol_path = "crate::network::protocol::Protocol"]
+pub struct GameAction {
+    pub kind: Property<GameActionKind>,
+    pub color: Property<u8>,
+    pub value: Property<u8>,
+}
+
+impl GameAction {
+    pub fn play_card(card: Card) -> Self {
+        GameAction::new_complete(GameActionKind::PlayCard, card.color as u8, card.value as u8)
+    }
+
+    pub fn draw_card() -> Self {
+        GameAction::with_card(GameActionKind::DrawCard, Card::back())
+    }
+
+    pub fn choose_color(color: Color) -> Self {
+        GameAction::with_card(
+            GameActionKind::ChooseColor,
+            Card {
+                color,
+                ..Card::back()
+            },
+        )
+    }
+
+    pub fn uno() -> Self {
+        GameAction::with_card(GameActionKind::Uno, Card::back())
+    }
+
+    pub fn counter_uno() -> Self {
+        GameAction::with_card(GameActionKind::CounterUno, Card::back())
+    }
+
+    pub fn exit_game() -> Self {
+        GameAction::with_card(GameActionKind::ExitGame, Card::back())
+    }
+
+    pub fn request_snapshot() -> Self {
+        GameAction::with_card(GameActionKind::RequestSnapshot, Card::back())
+    }
+
+    pub fn play_again() -> Self {
+        GameAction::with_card(GameActionKind::PlayAgain, Card::back())
+    }
+
+    pub fn card(&self) -> Card {
+        (*self.color, *self.value).into()
+    }
+
+    fn with_card(kind: GameActionKind, card: Card) -> Self {
+        GameAction::new_complete(kind, card.color as u8, card.value as u8)
+    }
+}
@@ -1,15 +1,48 @@
+use crate::{card::Card, network::protocol::NetworkCard, Player};
 use bevy_ecs::prelude::Component;
+use naia_shared::{derive_serde, serde, Property, Replicate};
 
-use naia_shared::{Property, Replicate};
+// How the round went for one player. `hand` holds the cards left in their hand, counted in
+// their score, and `round_score` the points they got this round
+#[derive_serde]
+pub struct RoundResult {
+    pub id: u64,
+    pub hand: Vec<NetworkCard>,
+    pub round_score: u32,
+    pub total_score: u32,
+    pub turns: u32,
+    pub cards_played: u32,
+    pub penalties: u32,
+}
+
+impl RoundResult {
+    /// Result of a player whose score has already been updated with the points of the round
+    pub fn new(id: u64, player: &Player, round_score: u32) -> Self {
+        RoundResult {
+            id,
+            hand: player.hand.iter().map(|&card| card.into()).collect(),
+            round_score,
+            total_score: player.score,
+            turns: player.round.turns,
+            cards_played: player.round.cards_played,
+            penalties: player.round.penalties,
+        }
+    }
+
+    pub fn hand(&self) -> Vec<Card> {
+        self.hand.iter().map(Card::from).collect()
+    }
+}
 
+/// Sent to every player when someone has played their last card, with the results of everyone
 #[derive(Component, Replicate)]
 #[protocol_path = "crate::network::protocol::Protocol"]
 pub struct GameEnd {
-    pub _p: Property<()>,
+    pub results: Property<Vec<RoundResult>>,
 }
 
 impl GameEnd {
-    pub fn new() -> Self {
-        GameEnd::new_complete(())
+    pub fn new(results: Vec<RoundResult>) -> Self {
+        GameEnd::new_complete(results)
     }
 }
@@ -0,0 +1,54 @@
+use super::NetworkCard;
+use crate::card::{Card, Color};
+use bevy_ecs::prelude::Component;
+use naia_shared::{derive_serde, serde, Property, Replicate};
+
+// With `CounterUno`, the player caught the target, who didn't call Uno in time
+#[derive_serde]
+pub enum GameLogKind {
+    PlayedCard { card: NetworkCard },
+    ChoseColor { color: u8 },
+    Uno,
+    CounterUno,
+}
+
+/// Something a player did in a game, sent to every player of the game for their action feed
+#[derive(Component, Replicate)]
+#[protocol_path = "crate::network::protocol::Protocol"]
+pub struct GameLog {
+    pub player_id: Property<u64>,
+    pub kind: Property<GameLogKind>,
+    /// Player affected by the action: skipped or drawing because of the card played, or caught
+    /// by a counter-Uno
+    pub target_id: Property<Option<u64>>,
+}
+
+impl GameLog {
+    pub fn new(player_id: u64, kind: GameLogKind, target_id: Option<u64>) -> Self {
+        GameLog::new_complete(player_id, kind, target_id)
+    }
+
+    pub fn played_card(player_id: u64, card: Card, target_id: Option<u64>) -> Self {
+        GameLog::new(
+            player_id,
+            GameLogKind::PlayedCard { card: card.into() },
+            target_id,
+        )
+    }
+
+    pub fn chose_color(player_id: u64, color: Color) -> Self {
+        GameLog::new(
+            player_id,
+            GameLogKind::ChoseColor { color: color as u8 },
+            None,
+        )
+    }
+
+    pub fn uno(player_id: u64) -> Self {
+        GameLog::new(player_id, GameLogKind::Uno, None)
+    }
+
+    pub fn counter_uno(player_id: u64, target_id: u64) -> Self {
+        GameLog::new(player_id, GameLogKind::CounterUno, Some(target_id))
+    }
+}
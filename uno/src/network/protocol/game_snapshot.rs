@@ -0,0 +1,76 @@
+use crate::card::{Card, Color};
+use bevy_ecs::prelude::Component;
+use naia_shared::{derive_serde, serde, Property, Replicate};
+
+#[derive_serde]
+pub struct NetworkCard {
+    pub color: u8,
+    pub value: u8,
+}
+
+impl From<Card> for NetworkCard {
+    fn from(card: Card) -> Self {
+        NetworkCard {
+            color: card.color as u8,
+            value: card.value as u8,
+        }
+    }
+}
+
+impl From<&NetworkCard> for Card {
+    fn from(card: &NetworkCard) -> Self {
+        (card.color, card.value).into()
+    }
+}
+
+#[derive_serde]
+pub struct SnapshotPlayer {
+    pub id: u64,
+    pub hand_size: usize,
+}
+
+/// Full state of a game as seen by one player, sent by the server so that the client can
+/// rebuild its table from scratch instead of relying on the stream of game messages
+#[derive(Component, Replicate)]
+#[protocol_path = "crate::network::protocol::Protocol"]
+pub struct GameSnapshot {
+    pub hand: Property<Vec<NetworkCard>>,
+    pub top_discard: Property<NetworkCard>,
+    pub current_color: Property<u8>,
+    /// Players of the game, in turn order
+    pub players: Property<Vec<SnapshotPlayer>>,
+    pub current_player: Property<u64>,
+    pub reverse_turn: Property<bool>,
+}
+
+impl GameSnapshot {
+    pub fn new(
+        hand: &[Card],
+        top_discard: Card,
+        current_color: Color,
+        players: Vec<SnapshotPlayer>,
+        current_player: u64,
+        reverse_turn: bool,
+    ) -> Self {
+        GameSnapshot::new_complete(
+            hand.iter().map(|&card| card.into()).collect(),
+            top_discard.into(),
+            current_color as u8,
+            players,
+            current_player,
+            reverse_turn,
+        )
+    }
+
+    pub fn hand(&self) -> Vec<Card> {
+        self.hand.iter().map(Card::from).collect()
+    }
+
+    pub fn top_discard(&self) -> Card {
+        Card::from(&*self.top_discard)
+    }
+
+    pub fn current_color(&self) -> Color {
+        (*self.current_color).into()
+    }
+}
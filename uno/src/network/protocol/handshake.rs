@@ -0,0 +1,17 @@
+use crate::network::PROTOCOL_VERSION;
+use bevy_ecs::prelude::Component;
+use naia_shared::{Property, Replicate};
+
+/// Authentication message sent by a client when connecting to the server
+#[derive(Component, Replicate)]
+#[protocol_path = "crate::network::protocol::Protocol"]
+pub struct Handshake {
+    pub protocol_version: Property<u32>,
+    pub client_version: Property<String>,
+}
+
+impl Handshake {
+    pub fn new(client_version: String) -> Self {
+        Handshake::new_complete(PROTOCOL_VERSION, client_version)
+    }
+}
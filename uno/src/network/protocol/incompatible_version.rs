@@ -0,0 +1,18 @@
+use crate::network::PROTOCOL_VERSION;
+use bevy_ecs::prelude::Component;
+use naia_shared::{Property, Replicate};
+
+/// Sent to a client whose protocol version doesn't match the one of the server, so that it can
+/// tell the user to update instead of showing a generic rejection. Nothing else is sent to it
+#[derive(Component, Replicate)]
+#[protocol_path = "crate::network::protocol::Protocol"]
+pub struct IncompatibleVersion {
+    pub protocol_version: Property<u32>,
+    pub server_version: Property<String>,
+}
+
+impl IncompatibleVersion {
+    pub fn new(server_version: String) -> Self {
+        IncompatibleVersion::new_complete(PROTOCOL_VERSION, server_version)
+    }
+}
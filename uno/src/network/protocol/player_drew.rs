@@ -0,0 +1,31 @@
+use bevy_ecs::prelude::Component;
+use naia_shared::{derive_serde, serde, Property, Replicate};
+
+// Why a player draws cards: `Deal` for the cards dealt at the start of the game, `Voluntary`
+// when the player couldn't play and drew a card, `CounterUno` when they were caught without
+// calling Uno, `Timeout` when their turn ran out of time
+#[derive_serde]
+pub enum DrawReason {
+    Deal,
+    Voluntary,
+    DrawTwo,
+    WildFour,
+    CounterUno,
+    Timeout,
+}
+
+/// Cards drawn by a player, sent to every player of the game. Only the player drawing them is
+/// told which cards they are, with `DrawCard`
+#[derive(Component, Replicate)]
+#[protocol_path = "crate::network::protocol::Protocol"]
+pub struct PlayerDrew {
+    pub player_id: Property<u64>,
+    pub count: Property<u32>,
+    pub reason: Property<DrawReason>,
+}
+
+impl PlayerDrew {
+    pub fn new(player_id: u64, count: u32, reason: DrawReason) -> Self {
+        PlayerDrew::new_complete(player_id, count, reason)
+    }
+}
@@ -4,12 +4,12 @@ use naia_shared::{Property, Replicate};
 
 #[derive(Component, Replicate)]
 #[protocol_path = "crate::network::protocol::Protocol"]
-pub struct ChooseColor {
-    _p: Property<()>,
+pub struct SendChat {
+    pub text: Property<String>,
 }
 
-impl ChooseColor {
-    pub fn new() -> Self {
-        ChooseColor::new_complete(())
+impl SendChat {
+    pub fn new(text: String) -> Self {
+        SendChat::new_complete(text)
     }
 }
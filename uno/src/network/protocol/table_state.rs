@@ -0,0 +1,18 @@
+use bevy_ecs::prelude::Component;
+use naia_shared::{Property, Replicate};
+
+/// Public state of the table of a game, replicated to every player of the game
+#[derive(Component, Replicate)]
+#[protocol_path = "crate::network::protocol::Protocol"]
+pub struct TableState {
+    pub reverse_turn: Property<bool>,
+    pub draw_pile_size: Property<usize>,
+    pub discard_size: Property<usize>,
+    pub current_player: Property<u64>,
+}
+
+impl Default for TableState {
+    fn default() -> Self {
+        TableState::new_complete(false, 0, 0, 0)
+    }
+}
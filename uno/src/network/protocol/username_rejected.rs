@@ -0,0 +1,15 @@
+use crate::username::UsernameError;
+use bevy_ecs::prelude::Component;
+use naia_shared::{Property, Replicate};
+
+#[derive(Component, Replicate)]
+#[protocol_path = "crate::network::protocol::Protocol"]
+pub struct UsernameRejected {
+    pub error: Property<UsernameError>,
+}
+
+impl UsernameRejected {
+    pub fn new(error: UsernameError) -> Self {
+        UsernameRejected::new_complete(error)
+    }
+}
@@ -0,0 +1,17 @@
+use crate::error::UnoError;
+
+/// Version of the network protocol. It must be bumped every time a message or a replicated
+/// component is added, removed or modified, so that stale clients are rejected by the server
+pub const PROTOCOL_VERSION: u32 = 12;
+
+/// Check whether a client using `protocol_version` can talk to this build
+pub fn check_protocol_version(protocol_version: u32) -> Result<(), UnoError> {
+    if protocol_version == PROTOCOL_VERSION {
+        Ok(())
+    } else {
+        Err(UnoError::IncompatibleProtocol {
+            server: PROTOCOL_VERSION,
+            client: protocol_version,
+        })
+    }
+}
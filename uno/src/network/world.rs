@@ -67,7 +67,7 @@ impl<'a, P: Protocolize, R: ReplicateSafe<P>> ReplicaRefTrait<P, R> for Componen
 
 impl<'a, P: Protocolize, R: ReplicateSafe<P>> ReplicaMutTrait<P, R> for ComponentMut<'a, P, R> {
     fn to_mut(&mut self) -> &mut R {
-        &mut self.inner
+        &mut *self.inner
     }
 }
 
@@ -99,6 +99,23 @@ impl<P: Protocolize> World<P> {
     pub fn proxy_mut<'w>(&'w mut self) -> WorldMut<'w, P> {
         WorldMut::<'w, P>::new(self)
     }
+
+    /// Get the component of type `R` of an entity
+    pub fn get<R: ReplicateSafe<P>>(&self, entity: &Entity) -> Option<&R> {
+        self.entities
+            .get(entity)?
+            .get(&Protocolize::kind_of::<R>())?
+            .cast_ref::<R>()
+    }
+
+    /// Iterate over every component of type `R`, whatever the entity holding it
+    pub fn query<R: ReplicateSafe<P>>(&self) -> impl Iterator<Item = &R> + '_ {
+        self.entities.iter().filter_map(|(_, component_map)| {
+            component_map
+                .get(&Protocolize::kind_of::<R>())?
+                .cast_ref::<R>()
+        })
+    }
 }
 
 // WorldRef //
@@ -144,7 +161,10 @@ impl<'w, P: Protocolize> WorldRefType<P, Entity> for WorldRef<'w, P> {
         has_component_of_type(self.world, entity, component_type)
     }
 
-    fn component<R: ReplicateSafe<P>>(&self, entity: &Entity) -> Option<ReplicaRefWrapper<P, R>> {
+    fn component<R: ReplicateSafe<P>>(
+        &self,
+        entity: &Entity,
+    ) -> Option<ReplicaRefWrapper<'_, P, R>> {
         component(self.world, entity)
     }
 
@@ -174,7 +194,10 @@ impl<'w, P: Protocolize> WorldRefType<P, Entity> for WorldMut<'w, P> {
         has_component_of_type(self.world, entity, component_type)
     }
 
-    fn component<R: ReplicateSafe<P>>(&self, entity: &Entity) -> Option<ReplicaRefWrapper<P, R>> {
+    fn component<R: ReplicateSafe<P>>(
+        &self,
+        entity: &Entity,
+    ) -> Option<ReplicaRefWrapper<'_, P, R>> {
         component(self.world, entity)
     }
 
@@ -232,7 +255,7 @@ impl<'w, P: Protocolize> WorldMutType<P, Entity> for WorldMut<'w, P> {
     fn component_mut<R: ReplicateSafe<P>>(
         &mut self,
         entity: &Entity,
-    ) -> Option<ReplicaMutWrapper<P, R>> {
+    ) -> Option<ReplicaMutWrapper<'_, P, R>> {
         if let Some(component_map) = self.world.entities.get_mut(entity) {
             if let Some(component_protocol) = component_map.get_mut(&Protocolize::kind_of::<R>()) {
                 if let Some(raw_ref) = component_protocol.cast_mut::<R>() {
@@ -21,6 +21,16 @@ pub enum PlayerState {
     Uno,
 }
 
+/// What a player did during the current round, shown at the end of the game
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RoundStats {
+    /// Turns the player got to play, not counting the skipped ones
+    pub turns: u32,
+    pub cards_played: u32,
+    /// Cards the player had to draw because of the cards of the others or a counter Uno
+    pub penalties: u32,
+}
+
 /// Structure to define a Uno player
 #[derive(Component, Clone, Debug)]
 pub struct Player {
@@ -29,6 +39,7 @@ pub struct Player {
     pub username: String,
     pub state: PlayerState,
     pub is_playing: bool,
+    pub round: RoundStats,
 }
 
 impl Player {
@@ -39,6 +50,7 @@ impl Player {
             score: 0,
             username,
             is_playing: false,
+            round: RoundStats::default(),
         }
     }
 
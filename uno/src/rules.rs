@@ -0,0 +1,676 @@
+//! The rules of a game. The server and the offline games of the client both run a
+//! `GameRules`, which gives the messages each player has to receive instead of sending them
+
+use crate::{
+    card::{Card, Color, Value},
+    network::{
+        protocol::{
+            CardPlayed, CardValidation, CounterUno, DrawCard, DrawReason, GameAction,
+            GameActionKind, GameEnd, GameLog, GameSnapshot, HaveToDrawCard, PlayerDrew,
+            RoundResult, SnapshotPlayer, StopUno, Uno,
+        },
+        Protocol,
+    },
+    player::PlayerState,
+    Deck, Player,
+};
+use naia_shared::ReplicateSafe;
+use rand::Rng;
+
+/// Number of cards dealt to each player
+pub const INITIAL_CARDS: usize = 7;
+
+#[derive(Clone)]
+pub struct Seat {
+    /// Id of the player, the one of their `NetworkPlayer` on the server
+    pub id: u64,
+    pub player: Player,
+}
+
+#[derive(Clone)]
+pub struct GameRules {
+    pub seats: Vec<Seat>,
+    current_color: Color,
+    deck: Deck,
+    discard: Deck,
+    turn_index: usize,
+    /// Number of turns since the start of the game, to tell the turns apart when the same player
+    /// plays twice in a row
+    turn: u32,
+    reverse_turn: bool,
+    /// Someone has played their last card, the players are looking at the results
+    over: bool,
+    /// Messages to deliver, with the index of the seat they are for
+    outbox: Vec<(usize, Protocol)>,
+}
+
+impl GameRules {
+    /// Game between players who have no id yet, they get the ids 1, 2, 3... in the order of
+    /// `usernames`
+    pub fn new(usernames: Vec<String>) -> GameRules {
+        GameRules::with_players(
+            usernames
+                .into_iter()
+                .enumerate()
+                .map(|(i, username)| (i as u64 + 1, Player::new(username)))
+                .collect(),
+        )
+    }
+
+    /// Shuffle the deck, deal the cards and pick the first player randomly. The players keep
+    /// their scores, everything else starts over
+    pub fn with_players(players: Vec<(u64, Player)>) -> GameRules {
+        assert!(!players.is_empty(), "A game needs at least one player");
+
+        let seats = players
+            .into_iter()
+            .map(|(id, player)| Seat {
+                id,
+                player: Player {
+                    score: player.score,
+                    ..Player::new(player.username)
+                },
+            })
+            .collect();
+
+        let mut game = GameRules {
+            seats,
+            current_color: Color::Black,
+            deck: Deck::full(),
+            discard: Deck::empty(),
+            turn_index: 0,
+            turn: 0,
+            reverse_turn: false,
+            over: false,
+            outbox: vec![],
+        };
+        game.deck.shuffle();
+
+        let mut first_card = game.draw_from_deck();
+        while first_card.color == Color::Black
+            || first_card.value == Value::Skip
+            || first_card.value == Value::Reverse
+            || first_card.value == Value::DrawTwo
+        {
+            game.discard.add(first_card);
+            first_card = game.draw_from_deck();
+        }
+        game.discard.add(first_card);
+        game.current_color = first_card.color;
+
+        for seat in 0..game.seats.len() {
+            game.give_cards(seat, INITIAL_CARDS as u32, DrawReason::Deal);
+            game.send(seat, CardPlayed::new(first_card));
+        }
+
+        game.turn_index = rand::thread_rng().gen_range(0..game.seats.len());
+        game.pass_turn(false);
+
+        game
+    }
+
+    /// New round with the same players
+    pub fn next_round(&self) -> GameRules {
+        GameRules::with_players(
+            self.seats
+                .iter()
+                .map(|seat| (seat.id, seat.player.clone()))
+                .collect(),
+        )
+    }
+
+    /// Take the messages produced since the last call
+    pub fn messages(&mut self) -> Vec<(usize, Protocol)> {
+        std::mem::take(&mut self.outbox)
+    }
+
+    /// Index of the seat whose turn it is
+    pub fn current_seat(&self) -> usize {
+        self.turn_index
+    }
+
+    pub fn turn(&self) -> u32 {
+        self.turn
+    }
+
+    pub fn current_color(&self) -> Color {
+        self.current_color
+    }
+
+    pub fn top_discard(&self) -> Card {
+        *self.discard.top().unwrap()
+    }
+
+    pub fn reverse_turn(&self) -> bool {
+        self.reverse_turn
+    }
+
+    pub fn draw_pile_size(&self) -> usize {
+        self.deck.size()
+    }
+
+    pub fn discard_size(&self) -> usize {
+        self.discard.size()
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.over
+    }
+
+    /// Whether the player of a seat has to call Uno and hasn't done it yet
+    pub fn waiting_for_uno(&self, seat: usize) -> bool {
+        seat == self.turn_index
+            && matches!(
+                self.seats[seat].player.state,
+                PlayerState::Uno
+                    | PlayerState::ChoosingColorWildUno {
+                        uno_done: false,
+                        ..
+                    }
+                    | PlayerState::ChoosingColorWildFourUno {
+                        uno_done: false,
+                        ..
+                    }
+            )
+    }
+
+    /// Whether the player of a seat has played a wild card and has to choose a color
+    pub fn choosing_color(&self, seat: usize) -> bool {
+        seat == self.turn_index
+            && matches!(
+                self.seats[seat].player.state,
+                PlayerState::ChoosingColorWild
+                    | PlayerState::ChoosingColorWildFour
+                    | PlayerState::ChoosingColorWildUno {
+                        color_chosen: false,
+                        ..
+                    }
+                    | PlayerState::ChoosingColorWildFourUno {
+                        color_chosen: false,
+                        ..
+                    }
+            )
+    }
+
+    /// The state of the game as seen by the player of a seat
+    pub fn snapshot(&self, seat: usize) -> GameSnapshot {
+        let players = self
+            .seats
+            .iter()
+            .map(|seat| SnapshotPlayer {
+                id: seat.id,
+                hand_size: seat.player.hand.len(),
+            })
+            .collect();
+
+        GameSnapshot::new(
+            &self.seats[seat].player.hand,
+            self.top_discard(),
+            self.current_color,
+            players,
+            self.seats[self.turn_index].id,
+            self.reverse_turn,
+        )
+    }
+
+    /// The messages asking the player of a seat to act, to show them again when the player
+    /// sits back at the device
+    pub fn prompts(&self, seat: usize) -> Vec<Protocol> {
+        let mut prompts = vec![];
+
+        if seat == self.turn_index && self.seats[seat].player.state == PlayerState::DrawingCard {
+            prompts.push(HaveToDrawCard::new().into_protocol());
+        }
+        if self.waiting_for_uno(seat) {
+            prompts.push(Uno::new().into_protocol());
+        }
+
+        prompts
+    }
+
+    /// Apply an action of the player of a seat, returns whether the rules allowed it
+    pub fn apply(&mut self, seat: usize, action: &GameAction) -> bool {
+        if *action.kind == GameActionKind::RequestSnapshot {
+            let snapshot = self.snapshot(seat);
+            self.send(seat, snapshot);
+            return true;
+        }
+
+        if self.over {
+            return false;
+        }
+
+        match *action.kind {
+            GameActionKind::PlayCard => self.play_card(seat, action.card()),
+            GameActionKind::DrawCard => self.draw_card(seat),
+            GameActionKind::ChooseColor => self.choose_color(seat, action.card().color),
+            GameActionKind::Uno => self.uno(seat),
+            GameActionKind::CounterUno => self.counter_uno(seat),
+            // Leaving the game and starting a new round are handled by whoever runs it
+            GameActionKind::RequestSnapshot
+            | GameActionKind::ExitGame
+            | GameActionKind::PlayAgain => false,
+        }
+    }
+
+    /// End the turn of a player who took too long: they draw a card, a color they had to
+    /// choose is picked for them and the Uno they didn't call is dropped since they have more
+    /// than one card again. Returns whether there was a turn to end
+    pub fn time_out(&mut self) -> bool {
+        if self.over {
+            return false;
+        }
+
+        let seat = self.turn_index;
+        let turn = self.turn;
+        self.give_cards(seat, 1, DrawReason::Timeout);
+
+        if self.choosing_color(seat) {
+            let color = self.favorite_color(seat);
+            self.choose_color(seat, color);
+        }
+
+        // Choosing the color may already have ended the turn
+        if self.turn == turn {
+            if self.waiting_for_uno(seat) {
+                self.send_all(StopUno::new());
+
+                let (_, skip_turn) = self.close_uno();
+                self.pass_turn_after_uno(skip_turn);
+            } else {
+                self.pass_turn(false);
+            }
+        }
+
+        true
+    }
+
+    /// Remove a player who left the game. The turn goes to the next player if it was theirs
+    pub fn leave(&mut self, seat: usize) -> Seat {
+        let left = self.seats.remove(seat);
+
+        // The messages of the other seats follow them to their new index
+        self.outbox.retain(|(recipient, _)| *recipient != seat);
+        for (recipient, _) in &mut self.outbox {
+            if *recipient > seat {
+                *recipient -= 1;
+            }
+        }
+
+        if self.seats.is_empty() {
+            return left;
+        }
+
+        if seat < self.turn_index {
+            self.turn_index -= 1;
+        } else if seat == self.turn_index {
+            // Put the turn just before the next player, so that passing it gives it to them
+            self.turn_index = if self.reverse_turn {
+                seat % self.seats.len()
+            } else {
+                (seat + self.seats.len() - 1) % self.seats.len()
+            };
+
+            if !self.over {
+                self.pass_turn(false);
+                return left;
+            }
+        }
+
+        if !self.over {
+            for seat in 0..self.seats.len() {
+                let snapshot = self.snapshot(seat);
+                self.send(seat, snapshot);
+            }
+        }
+
+        left
+    }
+
+    fn send<M: ReplicateSafe<Protocol>>(&mut self, seat: usize, message: M) {
+        self.outbox.push((seat, message.into_protocol()));
+    }
+
+    fn send_all<M: ReplicateSafe<Protocol> + Clone>(&mut self, message: M) {
+        for seat in 0..self.seats.len() {
+            self.send(seat, message.clone());
+        }
+    }
+
+    fn draw_from_deck(&mut self) -> Card {
+        if self.deck.is_empty() {
+            let top_card = self.discard.draw().unwrap();
+            self.deck = self.discard.clone();
+            self.deck.shuffle();
+
+            self.discard = Deck::empty();
+            self.discard.add(top_card);
+        }
+
+        self.deck.draw().unwrap()
+    }
+
+    fn next_seat(&self) -> usize {
+        if self.reverse_turn {
+            if self.turn_index == 0 {
+                self.seats.len() - 1
+            } else {
+                self.turn_index - 1
+            }
+        } else {
+            (self.turn_index + 1) % self.seats.len()
+        }
+    }
+
+    /// Penalties are drawn right away, they only count in the statistics of the round
+    fn add_penalty(&mut self, seat: usize, cards: u32) {
+        self.seats[seat].player.round.penalties += cards;
+    }
+
+    /// Give cards to the player of a seat and tell everyone why they drew them
+    fn give_cards(&mut self, seat: usize, count: u32, reason: DrawReason) {
+        for _ in 0..count {
+            let card = self.draw_from_deck();
+            self.seats[seat].player.hand.push(card);
+            self.send(seat, DrawCard::new(card));
+        }
+
+        self.send_all(PlayerDrew::new(self.seats[seat].id, count, reason));
+    }
+
+    fn pass_turn(&mut self, skipping: bool) {
+        self.turn_index = self.next_seat();
+        self.turn += 1;
+
+        for (i, seat) in self.seats.iter_mut().enumerate() {
+            seat.player.is_playing = i == self.turn_index;
+            if seat.player.is_playing {
+                seat.player.state = PlayerState::WaitingToPlay;
+            }
+        }
+
+        if !skipping {
+            let top_discard = self.top_discard();
+            let player = &mut self.seats[self.turn_index].player;
+            player.round.turns += 1;
+
+            if player.can_play(top_discard, self.current_color) {
+                player.state = PlayerState::PlayingCard;
+            } else {
+                player.state = PlayerState::DrawingCard;
+                self.send(self.turn_index, HaveToDrawCard::new());
+            }
+        }
+
+        for seat in 0..self.seats.len() {
+            let snapshot = self.snapshot(seat);
+            self.send(seat, snapshot);
+        }
+    }
+
+    fn draw_card(&mut self, seat: usize) -> bool {
+        if seat != self.turn_index || self.seats[seat].player.state != PlayerState::DrawingCard {
+            return false;
+        }
+
+        self.give_cards(seat, 1, DrawReason::Voluntary);
+
+        let top_discard = self.top_discard();
+        let player = &mut self.seats[seat].player;
+        if player.can_play(top_discard, self.current_color) {
+            player.state = PlayerState::PlayingCard;
+        } else {
+            self.pass_turn(false);
+        }
+
+        true
+    }
+
+    fn play_card(&mut self, seat: usize, card: Card) -> bool {
+        let player = &self.seats[seat].player;
+        let valid = seat == self.turn_index
+            && player.state == PlayerState::PlayingCard
+            && card.can_be_played(self.top_discard(), self.current_color)
+            && player.hand.contains(&card);
+
+        self.send(seat, CardValidation::new(valid));
+        if !valid {
+            return false;
+        }
+
+        for other in 0..self.seats.len() {
+            if other != seat {
+                self.send(other, CardPlayed::new(card));
+            }
+        }
+
+        let target_id = match card.value {
+            Value::Skip | Value::DrawTwo | Value::WildFour => Some(self.seats[self.next_seat()].id),
+            _ => None,
+        };
+        self.send_all(GameLog::played_card(self.seats[seat].id, card, target_id));
+
+        let player = &mut self.seats[seat].player;
+        let card_index = player.hand.iter().position(|&c| c == card).unwrap();
+        player.hand.remove(card_index);
+        player.round.cards_played += 1;
+        self.discard.add(card);
+        self.current_color = card.color;
+
+        if player.hand.is_empty() {
+            self.end();
+            return true;
+        }
+
+        let in_uno = player.hand.len() == 1;
+        if in_uno {
+            player.state = PlayerState::Uno;
+
+            for other in 0..self.seats.len() {
+                if other == seat {
+                    self.send(other, Uno::new());
+                } else {
+                    self.send(other, CounterUno::new());
+                }
+            }
+        }
+
+        match card.value {
+            Value::Reverse => {
+                self.reverse_turn = !self.reverse_turn;
+
+                if self.seats.len() == 2 && !in_uno {
+                    self.pass_turn(true);
+                }
+            }
+            Value::DrawTwo => {
+                let next_seat = self.next_seat();
+                self.add_penalty(next_seat, 2);
+                self.give_cards(next_seat, 2, DrawReason::DrawTwo);
+
+                if !in_uno {
+                    self.pass_turn(true);
+                }
+            }
+            Value::Skip if !in_uno => self.pass_turn(true),
+            Value::Wild => {
+                self.seats[seat].player.state = if in_uno {
+                    PlayerState::ChoosingColorWildUno {
+                        uno_done: false,
+                        color_chosen: false,
+                    }
+                } else {
+                    PlayerState::ChoosingColorWild
+                };
+                return true;
+            }
+            Value::WildFour => {
+                self.seats[seat].player.state = if in_uno {
+                    PlayerState::ChoosingColorWildFourUno {
+                        uno_done: false,
+                        color_chosen: false,
+                    }
+                } else {
+                    PlayerState::ChoosingColorWildFour
+                };
+
+                let next_seat = self.next_seat();
+                self.add_penalty(next_seat, 4);
+                self.give_cards(next_seat, 4, DrawReason::WildFour);
+                return true;
+            }
+            _ => {}
+        }
+
+        if !in_uno {
+            self.pass_turn(false);
+        }
+
+        true
+    }
+
+    fn choose_color(&mut self, seat: usize, color: Color) -> bool {
+        if !self.choosing_color(seat) {
+            return false;
+        }
+
+        let (pass_turn, skip_turn) = match &mut self.seats[seat].player.state {
+            PlayerState::ChoosingColorWild => (true, false),
+            PlayerState::ChoosingColorWildFour => (true, true),
+            PlayerState::ChoosingColorWildUno {
+                uno_done,
+                color_chosen,
+            } => {
+                *color_chosen = true;
+                (*uno_done, false)
+            }
+            PlayerState::ChoosingColorWildFourUno {
+                uno_done,
+                color_chosen,
+            } => {
+                *color_chosen = true;
+                (*uno_done, true)
+            }
+            _ => return false,
+        };
+
+        self.current_color = color;
+        self.send_all(GameLog::chose_color(self.seats[seat].id, color));
+
+        if pass_turn {
+            if skip_turn {
+                self.pass_turn(true);
+            }
+            self.pass_turn(false);
+        }
+
+        true
+    }
+
+    /// Returns whether the turn can be passed and whether the next player has to be skipped
+    fn close_uno(&mut self) -> (bool, bool) {
+        match &mut self.seats[self.turn_index].player.state {
+            PlayerState::ChoosingColorWildUno {
+                uno_done,
+                color_chosen,
+            } => {
+                *uno_done = true;
+                (*color_chosen, false)
+            }
+            PlayerState::ChoosingColorWildFourUno {
+                uno_done,
+                color_chosen,
+            } => {
+                *uno_done = true;
+                (*color_chosen, true)
+            }
+            _ => (true, false),
+        }
+    }
+
+    fn uno(&mut self, seat: usize) -> bool {
+        if !self.waiting_for_uno(seat) {
+            return false;
+        }
+
+        self.send_all(StopUno::new());
+        self.send_all(GameLog::uno(self.seats[seat].id));
+
+        let (pass_turn, skip_turn) = self.close_uno();
+        if pass_turn {
+            self.pass_turn_after_uno(skip_turn);
+        }
+
+        true
+    }
+
+    /// Pass the turn of a player who played their second to last card, skipping the next player
+    /// if the card said so
+    fn pass_turn_after_uno(&mut self, skip_turn: bool) {
+        let top_value = self.top_discard().value;
+        if skip_turn
+            || top_value == Value::DrawTwo
+            || top_value == Value::Skip
+            || (self.seats.len() == 2 && top_value == Value::Reverse)
+        {
+            self.pass_turn(true);
+        }
+        self.pass_turn(false);
+    }
+
+    /// Color the player of a seat has the most cards of
+    fn favorite_color(&self, seat: usize) -> Color {
+        let hand = &self.seats[seat].player.hand;
+
+        [Color::Yellow, Color::Red, Color::Blue, Color::Green]
+            .into_iter()
+            .max_by_key(|&color| hand.iter().filter(|card| card.color == color).count())
+            .unwrap()
+    }
+
+    /// The player of `caller` noticed the current player didn't call Uno in time
+    fn counter_uno(&mut self, caller: usize) -> bool {
+        let seat = self.turn_index;
+        if !self.waiting_for_uno(seat) {
+            return false;
+        }
+
+        self.send_all(StopUno::new());
+        self.send_all(GameLog::counter_uno(
+            self.seats[caller].id,
+            self.seats[seat].id,
+        ));
+
+        let (pass_turn, skip_turn) = self.close_uno();
+
+        self.add_penalty(seat, 2);
+        self.give_cards(seat, 2, DrawReason::CounterUno);
+
+        if pass_turn {
+            if skip_turn || self.top_discard().value == Value::DrawTwo {
+                self.pass_turn(true);
+            }
+            self.pass_turn(false);
+        }
+
+        true
+    }
+
+    fn end(&mut self) {
+        self.over = true;
+
+        let results = self
+            .seats
+            .iter_mut()
+            .map(|seat| {
+                let round_score = seat.player.compute_score();
+                seat.player.is_playing = false;
+                seat.player.score += round_score;
+
+                RoundResult::new(seat.id, &seat.player, round_score)
+            })
+            .collect();
+
+        self.send_all(GameEnd::new(results));
+    }
+}
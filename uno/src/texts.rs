@@ -1,17 +1,41 @@
-use serde::Deserialize;
-use std::{collections::HashMap, fmt};
+//! Texts of the interface, in every language. English and French are embedded in the binary,
+//! more locales can be added at runtime by dropping a `<code>.ron` file in a locales directory
 
-#[derive(Deserialize, Debug, Copy, Clone, Eq, PartialEq, Hash)]
-pub enum Language {
-    Francais,
-    English,
-}
+use serde::{
+    de::{self, IntoDeserializer},
+    Deserialize, Deserializer, Serialize,
+};
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+/// Code of a language, like `en` or `fr`
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Hash)]
+#[serde(transparent)]
+pub struct Language(pub String);
+
+impl Language {
+    /// Language used when a text is missing from a locale
+    pub const FALLBACK: &'static str = "en";
+
+    pub fn english() -> Language {
+        Language(Language::FALLBACK.to_owned())
+    }
 
-impl fmt::Display for Language {
-    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        match self {
-            Language::Francais => write!(f, "Français"),
-            _ => write!(f, "{self:?}"),
+    /// Language of a system locale such as `fr_FR.UTF-8` or `en-US`
+    pub fn from_locale(locale: &str) -> Language {
+        let code = locale
+            .split(|c: char| !c.is_ascii_alphabetic())
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+
+        if code.is_empty() {
+            Language::english()
+        } else {
+            Language(code)
         }
     }
 }
@@ -26,42 +50,273 @@ pub enum TextId {
     JoinLobby,
     LeaveLobby,
     StartGame,
+    Quit,
+    // Connection
+    Server,
+    Connect,
+    Connecting,
+    Reconnect,
+    Disconnect,
+    RecentServers,
+    NotConnected,
+    ConnectionLost,
+    DisconnectedFrom,
+    // Offline game
+    OfflineGame,
+    Player,
+    Human,
+    Bot,
+    AddPlayer,
+    Cancel,
+    HandOver,
+    Ready,
     // Menu Settings
     Settings,
     Username,
     EnableAnimations,
     Language,
+    Controls,
+    Keyboard,
+    Gamepad,
+    PressKey,
+    ResetControls,
+    ColorblindMode,
+    HighContrast,
+    Palette,
+    PaletteClassic,
+    PaletteColorblindSafe,
+    PaletteHighContrast,
+    CardTheme,
+    Volume,
+    Music,
+    Mute,
     // Game
     DrawCard,
+    DrawPile,
+    YourTurn,
+    PlayerTurn,
     Uno,
     CounterUno,
     ChooseColor,
+    Play,
+    PreviousCard,
+    NextCard,
+    PlaySelectedCard,
+    Yellow,
+    Red,
+    Blue,
+    Green,
+    SortHand,
+    SortManual,
+    SortColor,
+    SortValue,
+    // Action feed
+    ActionFeed,
+    CardName,
+    Skip,
+    Reverse,
+    Wild,
+    WildFour,
+    LogPlayedCard,
+    LogSkipped,
+    LogReversed,
+    LogDrewCards,
+    LogDrewPenalty,
+    LogChoseColor,
+    LogUno,
+    LogCounterUno,
+    TurnTimeout,
     // End Game
     Score,
     RemainingCards,
     EndGameTitle,
     BackToMenu,
+    PlayAgain,
+    WaitingForPlayers,
+    RoundPoints,
+    Turns,
+    CardsPlayed,
+    Penalties,
+    // Chat
+    Chat,
+    SendChat,
     // Errors
     EnterUsername,
+    ConnectionRejected,
+    IncompatibleVersion,
+    UsernameEmpty,
+    UsernameTooLong,
+    UsernameInvalidCharacters,
+    UsernameAlreadyTaken,
+    UsernameBlocked,
+    Error,
+    Ok,
+}
+
+#[derive(Debug, Clone)]
+struct Locale {
+    /// Name of the language, in the language itself
+    name: String,
+    texts: HashMap<TextId, String>,
 }
 
-pub type Text = HashMap<Language, String>;
+/// Content of a locale file. The ids are read as they are written, a file made for another
+/// version of the game can have ids this one doesn't know
+#[derive(Deserialize)]
+struct LocaleFile {
+    name: String,
+    texts: HashMap<LocaleKey, String>,
+}
+
+#[derive(PartialEq, Eq, Hash)]
+struct LocaleKey(String);
+
+impl<'de> Deserialize<'de> for LocaleKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KeyVisitor;
 
-#[derive(Deserialize, Debug, Clone)]
-pub struct Texts(HashMap<TextId, Text>);
+        impl<'de> de::Visitor<'de> for KeyVisitor {
+            type Value = LocaleKey;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("the id of a text")
+            }
+
+            fn visit_str<E: de::Error>(self, id: &str) -> Result<LocaleKey, E> {
+                Ok(LocaleKey(id.to_owned()))
+            }
+        }
+
+        deserializer.deserialize_identifier(KeyVisitor)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Texts {
+    locales: HashMap<Language, Locale>,
+}
 
 impl Texts {
-    pub fn get_all() -> Texts {
-        Texts(
-            ron::de::from_bytes(include_bytes!(concat!(
-                env!("CARGO_MANIFEST_DIR"),
-                "/data/texts.ron"
-            )))
-            .unwrap(),
-        )
+    /// Texts embedded in the binary
+    pub fn builtin() -> Texts {
+        let mut texts = Texts {
+            locales: HashMap::new(),
+        };
+
+        for (code, content) in [
+            ("en", include_str!("../data/locales/en.ron")),
+            ("fr", include_str!("../data/locales/fr.ron")),
+        ] {
+            texts
+                .add_locale(Language(code.to_owned()), content)
+                .expect("Invalid builtin locale");
+        }
+
+        texts
+    }
+
+    /// Add a locale, replacing the texts already known for this language. The texts whose id
+    /// isn't known are skipped, their ids are returned
+    pub fn add_locale(&mut self, language: Language, content: &str) -> Result<Vec<String>, String> {
+        let file: LocaleFile = ron::de::from_str(content).map_err(|err| err.to_string())?;
+
+        let mut texts = HashMap::new();
+        let mut unknown_ids = vec![];
+        for (LocaleKey(id), text) in file.texts {
+            let text_id: Result<TextId, de::value::Error> =
+                TextId::deserialize(id.as_str().into_deserializer());
+
+            match text_id {
+                Ok(text_id) => {
+                    texts.insert(text_id, text);
+                }
+                Err(_) => unknown_ids.push(id),
+            }
+        }
+        unknown_ids.sort();
+
+        self.locales.insert(
+            language,
+            Locale {
+                name: file.name,
+                texts,
+            },
+        );
+
+        Ok(unknown_ids)
+    }
+
+    /// Add every `<code>.ron` locale of a directory. Returns the problems found with the files:
+    /// the ones that couldn't be loaded, and the texts that were skipped because their id isn't
+    /// known. The other files and texts are still added
+    pub fn load_dir(&mut self, dir: &Path) -> Vec<(PathBuf, String)> {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) => return vec![(dir.to_owned(), err.to_string())],
+        };
+
+        let mut problems = vec![];
+
+        for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+            let code = match (path.extension(), path.file_stem()) {
+                (Some(extension), Some(stem)) if extension == "ron" => {
+                    stem.to_string_lossy().into_owned()
+                }
+                _ => continue,
+            };
+
+            let result = fs::read_to_string(&path)
+                .map_err(|err| err.to_string())
+                .and_then(|content| self.add_locale(Language(code), &content));
+
+            match result {
+                Ok(unknown_ids) => {
+                    for id in unknown_ids {
+                        problems.push((path.clone(), format!("Unknown text {id}, skipped")));
+                    }
+                }
+                Err(err) => problems.push((path, err)),
+            }
+        }
+
+        problems
+    }
+
+    /// Known languages with their name, sorted by name
+    pub fn languages(&self) -> Vec<(&Language, &str)> {
+        let mut languages = self
+            .locales
+            .iter()
+            .map(|(language, locale)| (language, locale.name.as_str()))
+            .collect::<Vec<_>>();
+        languages.sort_by_key(|(_, name)| *name);
+
+        languages
+    }
+
+    /// Name of a language, its code if it isn't known
+    pub fn language_name<'a>(&'a self, language: &'a Language) -> &'a str {
+        self.locales
+            .get(language)
+            .map_or(language.0.as_str(), |locale| locale.name.as_str())
+    }
+
+    /// Text in the given language, in English if it's missing, and the id of the text if it's
+    /// missing in English too
+    pub fn get(&self, id: TextId, language: &Language) -> String {
+        [language, &Language::english()]
+            .into_iter()
+            .find_map(|language| self.locales.get(language)?.texts.get(&id))
+            .cloned()
+            .unwrap_or_else(|| format!("{id:?}"))
     }
 
-    pub fn get(&self, id: TextId, language: Language) -> String {
-        self.0[&id][&language].clone()
+    /// Text with its `{name}` placeholders replaced by the given values
+    pub fn format(&self, id: TextId, language: &Language, values: &[(&str, &str)]) -> String {
+        values
+            .iter()
+            .fold(self.get(id, language), |text, (name, value)| {
+                text.replace(&format!("{{{name}}}"), value)
+            })
     }
 }
@@ -0,0 +1,56 @@
+use crate::texts::TextId;
+use naia_shared::{derive_serde, serde};
+
+/// The maximum amount of characters in a username
+pub const MAX_USERNAME_LENGTH: usize = 20;
+
+#[derive_serde]
+pub enum UsernameError {
+    Empty,
+    TooLong,
+    InvalidCharacters,
+    AlreadyTaken,
+    Blocked,
+}
+
+impl UsernameError {
+    pub fn text_id(&self) -> TextId {
+        match self {
+            UsernameError::Empty => TextId::UsernameEmpty,
+            UsernameError::TooLong => TextId::UsernameTooLong,
+            UsernameError::InvalidCharacters => TextId::UsernameInvalidCharacters,
+            UsernameError::AlreadyTaken => TextId::UsernameAlreadyTaken,
+            UsernameError::Blocked => TextId::UsernameBlocked,
+        }
+    }
+}
+
+/// Trim a username and check it against the rules shared by the client and the server.
+/// Whether the username is already taken can only be checked by the server
+pub fn validate_username(username: &str, blocklist: &[String]) -> Result<String, UsernameError> {
+    let username = username.trim();
+
+    if username.is_empty() {
+        Err(UsernameError::Empty)
+    } else if username.chars().count() > MAX_USERNAME_LENGTH {
+        Err(UsernameError::TooLong)
+    } else if username
+        .chars()
+        .any(|c| c.is_control() || (c.is_whitespace() && c != ' '))
+    {
+        Err(UsernameError::InvalidCharacters)
+    } else if is_blocked(username, blocklist) {
+        Err(UsernameError::Blocked)
+    } else {
+        Ok(username.to_owned())
+    }
+}
+
+fn is_blocked(username: &str, blocklist: &[String]) -> bool {
+    let username = username.to_lowercase();
+
+    blocklist
+        .iter()
+        .filter(|word| !word.is_empty())
+        .any(|word| username.contains(&word.to_lowercase()))
+}